@@ -0,0 +1,247 @@
+use crate::budget::Budget;
+use crate::graph::{Goal, Graph, GraphPath, Reversed};
+use crate::search::{Priority, SearchState};
+use crate::SearchOutcome;
+
+///
+/// Search graph from start and end at the same time, alternating one expansion forwards with one backwards
+///
+/// forward_goal: `Goal` -> Ends the forward search at end and estimates the distance to it
+///
+/// backward_goal: `Goal` -> Ends the backward search at start and estimates the distance to it
+///
+/// Whenever either search reaches a node the other has already reached, the path through that node
+/// becomes a candidate. The lowest `f` on each open list is a lower bound on any path not yet seen,
+/// so once either bound reaches the best candidate no cheaper path can exist and the search stops
+///
+pub fn bidirectional_search<G, F, B>(
+    graph: &G,
+    start: G::Node,
+    end: G::Node,
+    forward_goal: &F,
+    backward_goal: &B,
+) -> GraphPath<G::Node>
+where
+    G: Graph,
+    F: Goal<G::Node>,
+    B: Goal<G::Node>,
+{
+    bidirectional_search_within(
+        graph,
+        start,
+        end,
+        forward_goal,
+        backward_goal,
+        Budget::default(),
+        false,
+    )
+}
+
+/// `bidirectional_search` stopped by budget, which counts the expansions of both searches together.
+/// The cost limit applies to the forward search alone, which stops when the next node it would expand
+/// costs more than it, as a one-directional search does.
+/// A search that runs out returns the cheapest path found so far, or failing that the path towards
+/// end from the node the forward search came closest to it at. With partial an unreachable end
+/// returns that path too
+pub(crate) fn bidirectional_search_within<G, F, B>(
+    graph: &G,
+    start: G::Node,
+    end: G::Node,
+    forward_goal: &F,
+    backward_goal: &B,
+    budget: Budget,
+    partial: bool,
+) -> GraphPath<G::Node>
+where
+    G: Graph,
+    F: Goal<G::Node>,
+    B: Goal<G::Node>,
+{
+    let backward_graph = Reversed(graph);
+    let mut forward = SearchState::new(graph, start, forward_goal, Priority::GPlusH);
+    let mut backward = SearchState::new(&backward_graph, end, backward_goal, Priority::GPlusH);
+    forward.partial = partial;
+    forward.budget = budget.cost_limit();
+
+    // The cheapest path found so far, as its cost and the index where the two searches meet
+    let mut best: Option<(i32, usize)> = None;
+    let mut reached = vec![graph.index(start)];
+    let mut forward_turn = true;
+    let mut exhausted = false;
+
+    loop {
+        for index in reached.drain(..) {
+            let (forward_g, backward_g) = (forward.best_g[index], backward.best_g[index]);
+            if forward_g == i32::MAX || backward_g == i32::MAX {
+                continue;
+            }
+            let cost = forward_g.saturating_add(backward_g);
+            if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                best = Some((cost, index));
+            }
+        }
+
+        if forward.outcome.is_some() || backward.outcome.is_some() {
+            break;
+        }
+
+        // Every path not yet found costs at least bound, until one of the searches runs out of nodes
+        let bound = lowest_f(&forward).max(lowest_f(&backward));
+        if best.is_some_and(|(best_cost, _)| bound >= best_cost) {
+            break;
+        }
+        if budget.exhausted(forward.expanded + backward.expanded) {
+            exhausted = true;
+            break;
+        }
+
+        let mut record = |successors: Vec<(G::Node, i32)>| {
+            reached.extend(successors.iter().map(|(node, _)| graph.index(*node)));
+            successors
+        };
+        if forward_turn {
+            forward.step_with(graph, forward_goal, |node, _| {
+                record(graph.successors(node).collect())
+            });
+        } else {
+            backward.step_with(&backward_graph, backward_goal, |node, _| {
+                record(graph.predecessors(node).collect())
+            });
+        }
+        forward_turn = !forward_turn;
+    }
+
+    // The forward search stops at the cost limit only once every path not yet found costs more than it
+    let too_costly = best.is_some_and(|(cost, _)| budget.too_costly(cost));
+    let best = best.filter(|_| !too_costly);
+
+    // The backward search may have run out first, so a partial path needs the forward one to finish
+    if best.is_none() && partial && !exhausted {
+        while forward.outcome.is_none() {
+            if budget.exhausted(forward.expanded + backward.expanded) {
+                exhausted = true;
+                break;
+            }
+            forward.step(graph, forward_goal);
+        }
+    }
+
+    let expanded = forward.expanded + backward.expanded;
+    let Some((cost, meeting)) = best else {
+        let cost_exceeded = too_costly || forward.outcome == Some(SearchOutcome::BudgetExceeded);
+        forward.outcome = Some(if exhausted || cost_exceeded {
+            SearchOutcome::BudgetExceeded
+        } else {
+            SearchOutcome::Unreachable
+        });
+        return GraphPath {
+            expanded,
+            ..forward.path(graph)
+        };
+    };
+
+    // Walk back to start from where the searches meet, then on to end
+    let mut path = Vec::new();
+    let mut parent = Some(meeting);
+    while let Some(index) = parent {
+        path.push(graph.node(index));
+        parent = forward.parents[index];
+    }
+    path.reverse();
+
+    let mut parent = backward.parents[meeting];
+    while let Some(index) = parent {
+        path.push(graph.node(index));
+        parent = backward.parents[index];
+    }
+
+    GraphPath {
+        outcome: if exhausted {
+            SearchOutcome::BudgetExceeded
+        } else {
+            SearchOutcome::Found
+        },
+        path,
+        cost,
+        expanded,
+    }
+}
+
+/// The lowest `f` on the open list, or `i32::MAX` once it is empty
+fn lowest_f(state: &SearchState) -> i32 {
+    state.open.peek().map_or(i32::MAX, |entry| entry.f)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{dijkstra, random_grid, walk, Rng, MOVEMENTS};
+    use crate::{Algorithm, SearchOptions, SearchOutcome};
+
+    #[test]
+    fn cost_limit_matches_a_star() {
+        let mut rng = Rng::new(19);
+        for _ in 0..1000 {
+            let costs = rng.one_in(2);
+            let grid = random_grid(&mut rng, 16, 16, 4, costs);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            let max_cost = Some(rng.range(0, 200));
+            let partial = rng.one_in(2);
+
+            for movement in MOVEMENTS {
+                let options = |algorithm| SearchOptions {
+                    algorithm,
+                    movement,
+                    max_cost,
+                    partial,
+                    ..SearchOptions::default()
+                };
+                let a_star = grid.find_path_with(start, end, &options(Algorithm::AStar));
+                let both = grid.find_path_with(start, end, &options(Algorithm::Bidirectional));
+                // Either search may learn an end is unreachable before reaching the cost limit
+                if dijkstra(&grid, movement, start, end).is_some() {
+                    assert_eq!(
+                        both.outcome, a_star.outcome,
+                        "{movement:?} from {start} to {end}"
+                    );
+                    assert_eq!(both.partial, a_star.partial);
+                } else {
+                    assert_ne!(both.outcome, SearchOutcome::Found);
+                }
+                if both.outcome == SearchOutcome::Found {
+                    assert_eq!(both.cost, a_star.cost);
+                }
+                if !both.path.is_empty() {
+                    assert_eq!(both.path.first(), Some(&start));
+                    assert_eq!(walk(&grid, movement, &both.path), both.cost);
+                    assert!(max_cost.is_some_and(|max| both.cost <= max));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_paths_that_stop_short_are_partial() {
+        let mut rng = Rng::new(91);
+        for _ in 0..1000 {
+            let grid = random_grid(&mut rng, 16, 16, 4, true);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            let max_expanded = Some(rng.range(1, 80) as usize);
+            let partial = rng.one_in(2);
+
+            for movement in MOVEMENTS {
+                let options = SearchOptions {
+                    algorithm: Algorithm::Bidirectional,
+                    movement,
+                    max_expanded,
+                    partial,
+                    ..SearchOptions::default()
+                };
+                let result = grid.find_path_with(start, end, &options);
+                if !result.path.is_empty() {
+                    assert_eq!(result.partial, result.path.last() != Some(&end));
+                    assert_eq!(walk(&grid, movement, &result.path), result.cost);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::SearchOptions;
+
+/// How many expansions pass between checks of the clock, which is slow to read from wasm
+const CLOCK_INTERVAL: usize = 64;
+
+///
+/// A Budget holds the limits a search must stop at, taken from its SearchOptions
+///
+/// max_expanded: `Option<usize>` -> The most Nodes the search may expand
+///
+/// max_cost: `Option<i32>` -> The highest `g` the search may expand a Node at
+///
+/// deadline: `Option<f64>` -> When the search must stop, in milliseconds since the Unix epoch
+///
+/// remaining: `Option<f64>` -> Milliseconds left of the time limit while the Budget is paused
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Budget {
+    max_expanded: Option<usize>,
+    max_cost: Option<i32>,
+    deadline: Option<f64>,
+    remaining: Option<f64>,
+}
+
+impl Budget {
+    /// The limits in options, with the time limit starting now
+    pub(crate) fn new(options: &SearchOptions) -> Self {
+        Self {
+            max_expanded: options.max_expanded,
+            max_cost: options.max_cost,
+            deadline: options.time_limit.map(|limit| now() + limit),
+            remaining: None,
+        }
+    }
+
+    /// Stop the time limit running, for a search that waits between steps
+    pub(crate) fn pause(&mut self) {
+        if let Some(deadline) = self.deadline.take() {
+            self.remaining = Some(deadline - now());
+        }
+    }
+
+    /// Start the time limit running again from where `pause` stopped it
+    pub(crate) fn resume(&mut self) {
+        if let Some(remaining) = self.remaining.take() {
+            self.deadline = Some(now() + remaining);
+        }
+    }
+
+    /// Only the cost limit of this Budget, for a search whose other limits are counted elsewhere
+    pub(crate) fn cost_limit(&self) -> Self {
+        Self {
+            max_cost: self.max_cost,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a search that has expanded this many Nodes must stop before expanding another
+    pub(crate) fn exhausted(&self, expanded: usize) -> bool {
+        if self.max_expanded.is_some_and(|max| expanded >= max) {
+            return true;
+        }
+        expanded.is_multiple_of(CLOCK_INTERVAL)
+            && self.deadline.is_some_and(|deadline| now() >= deadline)
+    }
+
+    /// Whether a Node reached at cost g is too expensive to expand
+    pub(crate) fn too_costly(&self, g: i32) -> bool {
+        self.max_cost.is_some_and(|max| g > max)
+    }
+}
+
+/// Milliseconds since the Unix epoch
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Milliseconds since the Unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
+
+/// wasm32 has no clock without JavaScript, so time limits never expire
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+fn now() -> f64 {
+    0.0
+}
@@ -0,0 +1,157 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::grid::Grid;
+use crate::Point;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Grid {
+    /// The clearance of every cell as a row-major Uint32Array: the size of the largest square of walkable cells
+    /// with that cell at its top left corner, or 0 for cells that are not walkable
+    pub fn clearance_map(&self) -> Vec<u32> {
+        self.clearance().to_vec()
+    }
+}
+
+impl Grid {
+    /// Whether an agent of agent_size cells square fits with its top left cell at x,y.
+    /// The clearance layer is only built for agents larger than one cell
+    pub(crate) fn fits(&self, x: i32, y: i32, agent_size: usize) -> bool {
+        if agent_size <= 1 {
+            return self.is_walkable(x, y);
+        }
+        self.in_bounds(x, y) && self.clearance()[self.index(&Point { x, y })] as usize >= agent_size
+    }
+
+    /// The clearance layer, built the first time it is needed after the walls or costs change
+    fn clearance(&self) -> &[u32] {
+        self.clearance.get_or_init(|| {
+            let (width, height) = (self.width(), self.height());
+            let mut clearance = vec![0; self.len()];
+            for y in (0..height).rev() {
+                for x in (0..width).rev() {
+                    if !self.is_walkable(x as i32, y as i32) {
+                        continue;
+                    }
+                    // A square fits at x,y when one size smaller fits at the three cells below and to the right of it
+                    let at = |x: usize, y: usize| {
+                        if x < width && y < height {
+                            clearance[y * width + x]
+                        } else {
+                            0
+                        }
+                    };
+                    let smallest = at(x + 1, y).min(at(x, y + 1)).min(at(x + 1, y + 1));
+                    clearance[y * width + x] = smallest + 1;
+                }
+            }
+            clearance
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_grid, Rng, MOVEMENTS};
+    use crate::{Algorithm, AnyAngle, SearchOptions};
+
+    /// The grid an agent of size moves on, with a wall wherever it does not fit
+    fn walled(grid: &Grid, size: usize) -> Grid {
+        let mut walled = grid.clone();
+        for (index, clearance) in grid.clearance_map().into_iter().enumerate() {
+            if (clearance as usize) < size {
+                let pos = grid.point(index);
+                walled.set_wall(pos.x, pos.y);
+            }
+        }
+        walled
+    }
+
+    #[test]
+    fn agents_search_the_cells_they_fit_in() {
+        let mut rng = Rng::new(24);
+        for _ in 0..300 {
+            let costs = rng.one_in(2);
+            let grid = random_grid(&mut rng, 14, 14, 7, costs);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            let agent_size = rng.range(2, 5) as usize;
+            let reference = walled(&grid, agent_size);
+
+            for movement in MOVEMENTS {
+                for algorithm in [
+                    Algorithm::AStar,
+                    Algorithm::JumpPoint,
+                    Algorithm::Bidirectional,
+                ] {
+                    let options = SearchOptions {
+                        algorithm,
+                        movement,
+                        ..SearchOptions::default()
+                    };
+                    let agent = SearchOptions {
+                        agent_size,
+                        ..options
+                    };
+                    let expected = reference.find_path_with(start, end, &options);
+                    let result = grid.find_path_with(start, end, &agent);
+                    assert_eq!(
+                        result.outcome, expected.outcome,
+                        "{algorithm:?} {movement:?}"
+                    );
+                    assert_eq!(result.path, expected.path, "{algorithm:?} {movement:?}");
+                    assert_eq!(result.cost, expected.cost);
+                }
+
+                let options = SearchOptions {
+                    movement,
+                    ..SearchOptions::default()
+                };
+                let agent = SearchOptions {
+                    agent_size,
+                    ..options
+                };
+                let expected =
+                    reference.find_any_angle_path(start, end, AnyAngle::ThetaStar, &options);
+                let result = grid.find_any_angle_path(start, end, AnyAngle::ThetaStar, &agent);
+                assert_eq!(result.outcome, expected.outcome);
+                assert_eq!(result.path, expected.path);
+            }
+        }
+    }
+
+    #[test]
+    fn edits_clear_the_clearance_layer() {
+        let mut rng = Rng::new(42);
+        let mut grid = random_grid(&mut rng, 12, 12, 8, false);
+        let options = SearchOptions {
+            agent_size: 2,
+            ..SearchOptions::default()
+        };
+        for _ in 0..200 {
+            let pos = rng.point(&grid);
+            match rng.range(0, 12) {
+                0..=2 => grid.set_wall(pos.x, pos.y),
+                3..=5 => grid.clear_wall(pos.x, pos.y),
+                6 | 7 => {
+                    grid.toggle(pos.x, pos.y);
+                }
+                8 | 9 => grid.set_cost(pos.x, pos.y, rng.range(0, 3) as u32),
+                10 => {
+                    let costs = (0..grid.len()).map(|_| rng.range(0, 20).min(1) as u32);
+                    grid.set_costs(costs.collect());
+                }
+                _ => grid.clear_costs(),
+            }
+
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            let result = grid.find_path_with(start, end, &options);
+            let mut fresh = grid.clone();
+            fresh.clearance.take();
+            assert_eq!(grid.clearance_map(), fresh.clearance_map());
+            let expected = walled(&fresh, 2).find_path_with(start, end, &SearchOptions::default());
+            assert_eq!(result.outcome, expected.outcome);
+            assert_eq!(result.cost, expected.cost);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::graph::{Goal, Reversed};
+use crate::grid::Grid;
+use crate::movement::{Dirs, Movement};
+use crate::search::{Priority, SearchState};
+use crate::Point;
+
+/// Direction stored in a FlowField for cells that are a source, or cannot reach one
+pub const NO_DIRECTION: u8 = u8::MAX;
+
+///
+/// A FlowField holds the cost from every cell of a Grid to its nearest source, and the direction to step in to get there
+///
+/// Any number of units can follow the same FlowField towards the sources, looking up one cell per step
+/// instead of each running its own search
+///
+/// width: `usize` -> The number of columns in the grid
+///
+/// height: `usize` -> The number of rows in the grid
+///
+/// distances: `Vec<i32>` -> A row-major table with the cost of the cheapest path from each cell to a source,
+/// in the same units as `PathResult::cost`. Cells that cannot reach a source are -1
+///
+/// directions: `Vec<u8>` -> A row-major table with the first step of that path, numbered clockwise
+/// from 0 for north to 7 for north-west. Sources and cells that cannot reach one are `NO_DIRECTION`
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    pub width: usize,
+    pub height: usize,
+    pub distances: Vec<i32>,
+    pub directions: Vec<u8>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl FlowField {
+    /// Cost from x,y to its nearest source, or -1 if x,y is outside the grid or cannot reach one
+    pub fn distance(&self, x: i32, y: i32) -> i32 {
+        self.cell(x, y).map_or(-1, |index| self.distances[index])
+    }
+
+    /// The cell to step to from x,y on the way to its nearest source, if there is one
+    pub fn next_step(&self, x: i32, y: i32) -> Option<Point> {
+        let index = self.cell(x, y)?;
+        let dir = Dirs::iter().nth(usize::from(self.directions[index]))?;
+        Some(Point { x, y }.add(&Dirs::get(dir)))
+    }
+}
+
+impl FlowField {
+    fn cell(&self, x: i32, y: i32) -> Option<usize> {
+        let inside = x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height;
+        inside.then(|| y as usize * self.width + x as usize)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Grid {
+    /// Flood the grid outwards from sources, finding the cheapest path from every cell to its nearest source.
+    /// Sources that are walls or outside the grid are skipped
+    pub fn flow_field(&self, sources: Vec<Point>, movement: Movement) -> FlowField {
+        let graph = self.graph(movement);
+        let reversed = Reversed(&graph);
+        let starts = sources
+            .into_iter()
+            .filter(|source| self.is_walkable(source.x, source.y));
+
+        // The flood walks backwards so each cell's parent is the next cell on its way to a source
+        let mut state = SearchState::from_starts(&reversed, starts, &Flood, Priority::G);
+        while !state.step(&reversed, &Flood) {}
+
+        let directions = state
+            .parents
+            .iter()
+            .enumerate()
+            .map(|(index, parent)| {
+                let Some(next) = parent else {
+                    return NO_DIRECTION;
+                };
+                let (from, to) = (self.point(index), self.point(*next));
+                let step = Point {
+                    x: to.x - from.x,
+                    y: to.y - from.y,
+                };
+                Dirs::iter()
+                    .position(|dir| Dirs::get(dir) == step)
+                    .map_or(NO_DIRECTION, |dir| dir as u8)
+            })
+            .collect();
+        let distances = state
+            .best_g
+            .iter()
+            .map(|g| if *g == i32::MAX { -1 } else { *g })
+            .collect();
+
+        FlowField {
+            width: self.width(),
+            height: self.height(),
+            distances,
+            directions,
+        }
+    }
+
+    /// The cost from every cell to its nearest source as a row-major table, the `distances` of `flow_field`
+    pub fn distance_field(&self, sources: Vec<Point>, movement: Movement) -> Vec<i32> {
+        self.flow_field(sources, movement).distances
+    }
+}
+
+/// A Flood never ends a search early, so it runs until every reachable node is closed
+pub(crate) struct Flood;
+
+impl<N> Goal<N> for Flood {
+    fn is_goal(&self, _node: N) -> bool {
+        false
+    }
+
+    fn estimate(&self, _node: N) -> i32 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_grid, walk, Rng, MOVEMENTS};
+    use crate::{SearchOptions, SearchOutcome};
+
+    #[test]
+    fn distances_match_searches_to_the_nearest_source() {
+        let mut rng = Rng::new(18);
+        for _ in 0..100 {
+            let costs = rng.one_in(2);
+            let grid = random_grid(&mut rng, 10, 10, 4, costs);
+            let sources: Vec<Point> = (0..rng.range(1, 3)).map(|_| rng.point(&grid)).collect();
+
+            for movement in MOVEMENTS {
+                let options = SearchOptions {
+                    movement,
+                    ..SearchOptions::default()
+                };
+                let field = grid.flow_field(sources.clone(), movement);
+                for index in 0..grid.len() {
+                    let cell = grid.point(index);
+                    let expected = sources
+                        .iter()
+                        .map(|source| grid.find_path_with(cell, *source, &options))
+                        .filter(|result| result.outcome == SearchOutcome::Found)
+                        .map(|result| result.cost)
+                        .min()
+                        .unwrap_or(-1);
+                    let distance = field.distance(cell.x, cell.y);
+                    assert_eq!(distance, expected, "{movement:?} from {cell}");
+                    if distance == -1 {
+                        assert_eq!(field.next_step(cell.x, cell.y), None);
+                        continue;
+                    }
+
+                    // Following the field reaches a source at exactly the cost it promised
+                    let (mut path, mut pos) = (vec![cell], cell);
+                    while let Some(next) = field.next_step(pos.x, pos.y) {
+                        path.push(next);
+                        pos = next;
+                        assert!(path.len() <= grid.len());
+                    }
+                    assert!(sources.contains(&pos));
+                    assert_eq!(walk(&grid, movement, &path), distance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cells_outside_the_grid_have_no_distance() {
+        let grid = Grid::new(3, 3);
+        let field = grid.flow_field(
+            vec![Point { x: 1, y: 1 }, Point { x: 5, y: 5 }],
+            Movement::EightWay,
+        );
+        assert_eq!(field.distance(-1, 0), -1);
+        assert_eq!(field.distance(3, 0), -1);
+        assert_eq!(field.next_step(0, 3), None);
+        assert_eq!(field.distance(1, 1), 0);
+        assert_eq!(field.next_step(1, 1), None);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::search::{Priority, SearchState};
+use crate::SearchOutcome;
+
+///
+/// A Graph is anything the search can walk: a grid, a navmesh, a road network, a hex map
+///
+/// Node: `Copy` -> The identifier of a node in the graph
+///
+/// Every node maps to a distinct index below `node_count`, which the search uses for its tables
+///
+pub trait Graph {
+    type Node: Copy + PartialEq;
+
+    /// Number of indices the search needs to reserve, one past the largest `index`
+    fn node_count(&self) -> usize;
+
+    /// The index of a node, below `node_count`
+    fn index(&self, node: Self::Node) -> usize;
+
+    /// The node at an index, the inverse of `index`
+    fn node(&self, index: usize) -> Self::Node;
+
+    /// The nodes reachable in one step from node, with the cost of each step
+    fn successors(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, i32)>;
+
+    /// The nodes that reach node in one step, with the cost of each step. Only needed by searches
+    /// that walk the graph backwards, and the same as `successors` for graphs where every step can be reversed at the same cost
+    fn predecessors(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, i32)> {
+        self.successors(node)
+    }
+}
+
+/// Reversed walks a Graph backwards, following `predecessors` instead of `successors`
+pub struct Reversed<'a, G: Graph>(pub &'a G);
+
+impl<G: Graph> Graph for Reversed<'_, G> {
+    type Node = G::Node;
+
+    fn node_count(&self) -> usize {
+        self.0.node_count()
+    }
+
+    fn index(&self, node: Self::Node) -> usize {
+        self.0.index(node)
+    }
+
+    fn node(&self, index: usize) -> Self::Node {
+        self.0.node(index)
+    }
+
+    fn successors(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, i32)> {
+        self.0.predecessors(node)
+    }
+
+    fn predecessors(&self, node: Self::Node) -> impl Iterator<Item = (Self::Node, i32)> {
+        self.0.successors(node)
+    }
+}
+
+/// A Goal tells the search when to stop and how far a node is estimated to be from stopping
+pub trait Goal<N> {
+    fn is_goal(&self, node: N) -> bool;
+
+    /// Estimated cost from node to the nearest goal, never more than the real cost for an optimal path
+    fn estimate(&self, node: N) -> i32;
+}
+
+/// GraphPath is the outcome of a search over any Graph, with the same meaning as `PathResult`
+#[derive(Debug, Clone)]
+pub struct GraphPath<N> {
+    pub outcome: SearchOutcome,
+    pub path: Vec<N>,
+    pub cost: i32,
+    pub expanded: usize,
+}
+
+/// Search graph for the cheapest path from start to a node satisfying goal
+pub fn search<G: Graph, Q: Goal<G::Node>>(
+    graph: &G,
+    start: G::Node,
+    goal: &Q,
+) -> GraphPath<G::Node> {
+    let mut state = SearchState::new(graph, start, goal, Priority::GPlusH);
+    while !state.step(graph, goal) {}
+    state.path(graph)
+}
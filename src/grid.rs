@@ -0,0 +1,596 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use std::sync::OnceLock;
+
+use crate::bidirectional::bidirectional_search_within;
+use crate::budget::Budget;
+use crate::graph::{Goal, Graph};
+use crate::heuristic::{Estimate, Weighted};
+use crate::jps::{self, JumpPoints};
+use crate::movement::{self, Dirs, Movement};
+use crate::search::SearchState;
+use crate::{Algorithm, MultiGoalResult, PathResult, Point, SearchOptions, SearchOutcome};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+///
+/// A Grid is a rectangular map that lives in wasm memory between searches
+///
+/// width: `usize` -> The number of columns in the grid
+///
+/// height: `usize` -> The number of rows in the grid
+///
+/// walls: `Vec<u64>` -> A row-major bitset with one bit per cell, set when the cell is a wall
+///
+/// costs: `Vec<u32>` -> A row-major cost layer with the multiplier for entering each cell.
+/// Empty until a cost is set, in which case every cell costs 1
+///
+/// clearance: `OnceLock<Vec<u32>>` -> The `clearance_map`, built by the first search for an agent
+/// larger than one cell and kept until the walls or costs change
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    walls: Vec<u64>,
+    costs: Vec<u32>,
+    pub(crate) clearance: OnceLock<Vec<u32>>,
+}
+
+/// Cell cost marking a cell as impassable, in the same way as a wall
+pub const IMPASSABLE: u32 = 0;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Grid {
+    /// Create an empty grid with no walls
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            walls: vec![0; (width * height).div_ceil(WORD_BITS)],
+            costs: Vec::new(),
+            clearance: OnceLock::new(),
+        }
+    }
+
+    /// Create a grid with the given walls. Walls outside the grid are ignored
+    pub fn with_walls(width: usize, height: usize, walls: Vec<Point>) -> Self {
+        let mut grid = Self::new(width, height);
+        for wall in walls {
+            grid.set_wall(wall.x, wall.y);
+        }
+        grid
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether x,y lies inside the grid
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
+    }
+
+    /// Mark x,y as a wall. Does nothing if x,y is outside the grid
+    pub fn set_wall(&mut self, x: i32, y: i32) {
+        if self.in_bounds(x, y) {
+            let (word, bit) = self.bit(x, y);
+            self.walls[word] |= bit;
+            self.clearance.take();
+        }
+    }
+
+    /// Mark x,y as open. Does nothing if x,y is outside the grid
+    pub fn clear_wall(&mut self, x: i32, y: i32) {
+        if self.in_bounds(x, y) {
+            let (word, bit) = self.bit(x, y);
+            self.walls[word] &= !bit;
+            self.clearance.take();
+        }
+    }
+
+    /// Flip x,y between wall and open, returning whether it is now a wall
+    pub fn toggle(&mut self, x: i32, y: i32) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        let (word, bit) = self.bit(x, y);
+        self.walls[word] ^= bit;
+        self.clearance.take();
+        self.walls[word] & bit != 0
+    }
+
+    /// Whether x,y is inside the grid, not a wall and not `IMPASSABLE`
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        if !self.in_bounds(x, y) {
+            return false;
+        }
+        let (word, bit) = self.bit(x, y);
+        self.walls[word] & bit == 0 && self.cost(x, y) != IMPASSABLE
+    }
+
+    /// The cost multiplier for entering x,y. Cells outside the grid are `IMPASSABLE`
+    pub fn cost(&self, x: i32, y: i32) -> u32 {
+        if !self.in_bounds(x, y) {
+            return IMPASSABLE;
+        }
+        if self.costs.is_empty() {
+            return 1;
+        }
+        self.costs[self.index(&Point { x, y })]
+    }
+
+    /// Set the cost multiplier for entering x,y. Does nothing if x,y is outside the grid
+    pub fn set_cost(&mut self, x: i32, y: i32, cost: u32) {
+        if self.in_bounds(x, y) {
+            if self.costs.is_empty() {
+                self.costs = vec![1; self.len()];
+            }
+            let index = self.index(&Point { x, y });
+            self.costs[index] = cost;
+            self.clearance.take();
+        }
+    }
+
+    /// Replace every cell cost from a row-major Uint32Array, returning false if its length does not match the grid
+    pub fn set_costs(&mut self, costs: Vec<u32>) -> bool {
+        if costs.len() != self.len() {
+            return false;
+        }
+        self.costs = costs;
+        self.clearance.take();
+        true
+    }
+
+    /// Replace every cell cost from a row-major Uint8Array, returning false if its length does not match the grid
+    pub fn set_costs_u8(&mut self, costs: Vec<u8>) -> bool {
+        self.set_costs(costs.into_iter().map(u32::from).collect())
+    }
+
+    /// Reset every cell cost to 1
+    pub fn clear_costs(&mut self) {
+        self.costs = Vec::new();
+        self.clearance.take();
+    }
+
+    /// Change the dimensions of the grid, keeping the walls and costs of every cell that is still inside it
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let mut resized = Self::new(width, height);
+        for y in 0..self.height.min(height) as i32 {
+            for x in 0..self.width.min(width) as i32 {
+                let (word, bit) = self.bit(x, y);
+                if self.walls[word] & bit != 0 {
+                    resized.set_wall(x, y);
+                }
+                if !self.costs.is_empty() {
+                    resized.set_cost(x, y, self.cost(x, y));
+                }
+            }
+        }
+        *self = resized;
+    }
+
+    /// Search for the shortest path from start to end with the default SearchOptions
+    pub fn find_path(&self, start: Point, end: Point) -> PathResult {
+        self.find_path_with(start, end, &SearchOptions::default())
+    }
+
+    /// Search for the shortest path from start to end
+    pub fn find_path_with(&self, start: Point, end: Point, options: &SearchOptions) -> PathResult {
+        self.find_path_with_estimate(start, end, options, &options.heuristic())
+    }
+
+    /// Search for the shortest path from start to the nearest of ends. Ends that are walls or outside
+    /// the grid are skipped, and the search reports `GoalBlocked` when none are left,
+    /// or `OutOfBounds` when every end was outside the grid.
+    /// JumpPoint and Bidirectional searches need a single end, so they run as AStar
+    pub fn find_path_to_nearest(
+        &self,
+        start: Point,
+        ends: Vec<Point>,
+        options: &SearchOptions,
+    ) -> MultiGoalResult {
+        let walkable: Vec<Point> = ends
+            .iter()
+            .copied()
+            .filter(|end| self.fits(end.x, end.y, options.agent_size))
+            .collect();
+        if walkable.is_empty() {
+            let out_of_bounds =
+                !ends.is_empty() && ends.iter().all(|end| !self.in_bounds(end.x, end.y));
+            let outcome = self
+                .check_agent_endpoints(&start, &start, options.agent_size)
+                .unwrap_or(if out_of_bounds {
+                    SearchOutcome::OutOfBounds
+                } else {
+                    SearchOutcome::GoalBlocked
+                });
+            return MultiGoalResult::new(PathResult::failed(outcome, 0), &ends);
+        }
+
+        let heuristic = options.heuristic();
+        let weighted = Weighted {
+            estimate: &heuristic,
+            weight: options.weight,
+        };
+        let goal = PointsGoal {
+            ends: &walkable,
+            estimate: &weighted,
+        };
+        MultiGoalResult::new(self.find_path_to_goal(start, &goal, options), &ends)
+    }
+}
+
+impl Grid {
+    /// Search for the shortest path from start to end using a custom Estimate for `h`,
+    /// scaled by the weight in options. The heuristic in options is ignored
+    pub fn find_path_with_estimate<E: Estimate + ?Sized>(
+        &self,
+        start: Point,
+        end: Point,
+        options: &SearchOptions,
+        estimate: &E,
+    ) -> PathResult {
+        if let Some(outcome) = self.check_agent_endpoints(&start, &end, options.agent_size) {
+            return PathResult::failed(outcome, 0);
+        }
+
+        let weighted = Weighted {
+            estimate,
+            weight: options.weight,
+        };
+        let goal = PointGoal {
+            end,
+            estimate: &weighted,
+        };
+        if options.algorithm == Algorithm::Bidirectional {
+            let backward_goal = PointGoal {
+                end: start,
+                estimate: &weighted,
+            };
+            let graph = self.graph_for(options);
+            let budget = Budget::new(options);
+            let mut result: PathResult = bidirectional_search_within(
+                &graph,
+                start,
+                end,
+                &goal,
+                &backward_goal,
+                budget,
+                options.partial,
+            )
+            .into();
+            // Halves that met before the budget ran out return a whole path, just not a proven cheapest one
+            result.partial = result.path.last().is_some_and(|last| *last != end);
+            return result;
+        }
+
+        let mut state = SearchState::new(
+            &self.graph_for(options),
+            start,
+            &goal,
+            options.algorithm.priority(),
+        );
+        state.budget = Budget::new(options);
+        state.partial = options.partial;
+        while !self.step(&mut state, options, &goal) {}
+        self.path(&state, options)
+    }
+
+    /// Search for the shortest path from start to any Point satisfying goal, which also supplies `h`.
+    /// The heuristic and weight in options are ignored, and JumpPoint and Bidirectional searches run as AStar
+    pub fn find_path_to_goal<Q: Goal<Point>>(
+        &self,
+        start: Point,
+        goal: &Q,
+        options: &SearchOptions,
+    ) -> PathResult {
+        if let Some(outcome) = self.check_agent_endpoints(&start, &start, options.agent_size) {
+            return PathResult::failed(outcome, 0);
+        }
+
+        let graph = self.graph_for(options);
+        let mut state = SearchState::new(&graph, start, goal, options.algorithm.priority());
+        state.budget = Budget::new(options);
+        state.partial = options.partial;
+        while !state.step(&graph, goal) {}
+        state.path(&graph).into()
+    }
+
+    /// Expand the next Node of a search on this grid with the chosen Algorithm, returning whether it is finished
+    pub(crate) fn step<E: Estimate + ?Sized>(
+        &self,
+        state: &mut SearchState,
+        options: &SearchOptions,
+        goal: &PointGoal<E>,
+    ) -> bool {
+        let graph = self.graph_for(options);
+        if self.uses_jump_points(options) {
+            let jump_points = JumpPoints {
+                grid: self,
+                movement: options.movement,
+                agent_size: options.agent_size,
+                end: goal.end,
+            };
+            state.step_with(&graph, goal, |node, parent| {
+                jump_points.successors(node, parent)
+            })
+        } else {
+            state.step(&graph, goal)
+        }
+    }
+
+    /// The result of a finished search on this grid, with every cell of the path filled in
+    pub(crate) fn path(&self, state: &SearchState, options: &SearchOptions) -> PathResult {
+        let mut result: PathResult = state.path(&self.graph_for(options)).into();
+        if self.uses_jump_points(options) {
+            result.path = jps::expand_jumps(&result.path);
+        }
+        result
+    }
+
+    /// Whether a JumpPoint search can jump. Partial searches step cell by cell instead,
+    /// since jumps only ever expand the jump points and not the cells closest to the end
+    fn uses_jump_points(&self, options: &SearchOptions) -> bool {
+        options.algorithm == Algorithm::JumpPoint
+            && !options.partial
+            && self.costs.iter().all(|cost| *cost <= 1)
+    }
+
+    /// This grid as a Graph, moving between cells with the given Movement
+    pub fn graph(&self, movement: Movement) -> GridGraph<'_> {
+        GridGraph {
+            grid: self,
+            movement,
+            agent_size: 1,
+        }
+    }
+
+    /// This grid as a Graph for the movement and agent size in options
+    pub(crate) fn graph_for(&self, options: &SearchOptions) -> GridGraph<'_> {
+        GridGraph {
+            grid: self,
+            movement: options.movement,
+            agent_size: options.agent_size,
+        }
+    }
+
+    /// Why a search from start to end cannot begin, if it cannot
+    pub(crate) fn check_endpoints(&self, start: &Point, end: &Point) -> Option<SearchOutcome> {
+        self.check_agent_endpoints(start, end, 1)
+    }
+
+    /// Why a search from start to end for an agent of agent_size cells square cannot begin, if it cannot
+    pub(crate) fn check_agent_endpoints(
+        &self,
+        start: &Point,
+        end: &Point,
+        agent_size: usize,
+    ) -> Option<SearchOutcome> {
+        if !self.in_bounds(start.x, start.y) || !self.in_bounds(end.x, end.y) {
+            Some(SearchOutcome::OutOfBounds)
+        } else if !self.fits(start.x, start.y, agent_size) {
+            Some(SearchOutcome::StartBlocked)
+        } else if !self.fits(end.x, end.y, agent_size) {
+            Some(SearchOutcome::GoalBlocked)
+        } else {
+            None
+        }
+    }
+
+    /// Position of a Point in the row-major tables used by the search
+    pub(crate) fn index(&self, pos: &Point) -> usize {
+        pos.y as usize * self.width + pos.x as usize
+    }
+
+    /// Point at a position in the row-major tables used by the search
+    pub(crate) fn point(&self, index: usize) -> Point {
+        Point {
+            x: (index % self.width) as i32,
+            y: (index / self.width) as i32,
+        }
+    }
+
+    /// Number of cells in the grid
+    pub(crate) fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn bit(&self, x: i32, y: i32) -> (usize, u64) {
+        let index = self.index(&Point { x, y });
+        (index / WORD_BITS, 1 << (index % WORD_BITS))
+    }
+}
+
+///
+/// A GridGraph walks a Grid as a Graph
+///
+/// Each step costs `ORTHOGONAL_COST` or `DIAGONAL_COST`, times the cost of the cell entered
+///
+/// agent_size: `usize` -> The width and height in cells of the square agent walking the grid.
+/// Nodes are the top left cell of the agent, and only cells with a clearance of at least agent_size are entered
+///
+pub struct GridGraph<'a> {
+    pub grid: &'a Grid,
+    pub movement: Movement,
+    pub agent_size: usize,
+}
+
+impl Graph for GridGraph<'_> {
+    type Node = Point;
+
+    fn node_count(&self) -> usize {
+        self.grid.len()
+    }
+
+    fn index(&self, node: Point) -> usize {
+        self.grid.index(&node)
+    }
+
+    fn node(&self, index: usize) -> Point {
+        self.grid.point(index)
+    }
+
+    fn successors(&self, node: Point) -> impl Iterator<Item = (Point, i32)> {
+        self.movement
+            .dirs()
+            .filter(move |dir| {
+                self.movement
+                    .can_move(self.grid, &node, dir, self.agent_size)
+            })
+            .map(move |dir| {
+                let next = node.add(&Dirs::get(dir));
+                (next, self.step_cost(&node, &next))
+            })
+    }
+
+    /// Moves are allowed in both directions, but a step costs the cost of the cell entered, which differs when reversed
+    fn predecessors(&self, node: Point) -> impl Iterator<Item = (Point, i32)> {
+        self.movement
+            .dirs()
+            .filter(move |dir| {
+                self.movement
+                    .can_move(self.grid, &node, dir, self.agent_size)
+            })
+            .map(move |dir| {
+                let previous = node.add(&Dirs::get(dir));
+                (previous, self.step_cost(&previous, &node))
+            })
+    }
+}
+
+impl GridGraph<'_> {
+    fn step_cost(&self, from: &Point, to: &Point) -> i32 {
+        let cell_cost = self.grid.cost(to.x, to.y);
+        movement::step_cost(from, to).saturating_mul(i32::try_from(cell_cost).unwrap_or(i32::MAX))
+    }
+}
+
+/// A PointGoal ends a grid search at a single end Point, estimating the distance to it with an Estimate
+pub struct PointGoal<'a, E: Estimate + ?Sized> {
+    pub end: Point,
+    pub estimate: &'a E,
+}
+
+impl<E: Estimate + ?Sized> Goal<Point> for PointGoal<'_, E> {
+    fn is_goal(&self, node: Point) -> bool {
+        node == self.end
+    }
+
+    fn estimate(&self, node: Point) -> i32 {
+        self.estimate.estimate(&node, &self.end)
+    }
+}
+
+/// A PointsGoal ends a grid search at whichever of several end Points is reached first,
+/// estimating with the distance to the closest of them
+pub struct PointsGoal<'a, E: Estimate + ?Sized> {
+    pub ends: &'a [Point],
+    pub estimate: &'a E,
+}
+
+impl<E: Estimate + ?Sized> Goal<Point> for PointsGoal<'_, E> {
+    fn is_goal(&self, node: Point) -> bool {
+        self.ends.contains(&node)
+    }
+
+    fn estimate(&self, node: Point) -> i32 {
+        self.ends
+            .iter()
+            .map(|end| self.estimate.estimate(&node, end))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_grid, walk, Rng, MOVEMENTS};
+
+    #[test]
+    fn nearest_end_is_the_cheapest() {
+        let mut rng = Rng::new(17);
+        for _ in 0..300 {
+            let costs = rng.one_in(2);
+            let grid = random_grid(&mut rng, 12, 12, 4, costs);
+            let start = rng.point(&grid);
+            let ends: Vec<Point> = (0..rng.range(1, 5)).map(|_| rng.point(&grid)).collect();
+            if !grid.is_walkable(start.x, start.y) {
+                continue;
+            }
+
+            for movement in MOVEMENTS {
+                let options = SearchOptions {
+                    movement,
+                    ..SearchOptions::default()
+                };
+                let result = grid.find_path_to_nearest(start, ends.clone(), &options);
+                let cheapest = ends
+                    .iter()
+                    .map(|end| grid.find_path_with(start, *end, &options))
+                    .filter(|each| each.outcome == SearchOutcome::Found)
+                    .map(|each| each.cost)
+                    .min();
+                match cheapest {
+                    Some(cost) => {
+                        assert_eq!(result.outcome, SearchOutcome::Found, "{movement:?}");
+                        assert_eq!(result.cost, cost);
+                        let goal = result.goal.expect("a reached end");
+                        assert_eq!(result.path.last(), Some(&ends[goal]));
+                        assert_eq!(result.path.first(), Some(&start));
+                        assert_eq!(walk(&grid, movement, &result.path), cost);
+                    }
+                    None => {
+                        assert_ne!(result.outcome, SearchOutcome::Found, "{movement:?}");
+                        assert_eq!(result.goal, None);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_end_outcomes() {
+        let mut grid = Grid::new(4, 4);
+        grid.set_wall(3, 3);
+        let options = SearchOptions::default();
+        let start = Point { x: 0, y: 0 };
+        let outside = vec![Point { x: -1, y: 0 }, Point { x: 4, y: 2 }];
+
+        let nearest = |start, ends: &Vec<Point>| {
+            grid.find_path_to_nearest(start, ends.clone(), &options)
+                .outcome
+        };
+        assert_eq!(nearest(start, &vec![]), SearchOutcome::GoalBlocked);
+        assert_eq!(nearest(start, &outside), SearchOutcome::OutOfBounds);
+        assert_eq!(
+            nearest(start, &vec![Point { x: 3, y: 3 }, Point { x: 9, y: 9 }]),
+            SearchOutcome::GoalBlocked
+        );
+
+        let mixed = vec![
+            Point { x: -1, y: 0 },
+            Point { x: 3, y: 3 },
+            Point { x: 2, y: 0 },
+        ];
+        let result = grid.find_path_to_nearest(start, mixed, &options);
+        assert_eq!(result.outcome, SearchOutcome::Found);
+        assert_eq!(result.goal, Some(2));
+        assert_eq!(result.cost, 2 * movement::ORTHOGONAL_COST);
+
+        let blocked = Point { x: 3, y: 3 };
+        assert_eq!(nearest(blocked, &vec![start]), SearchOutcome::StartBlocked);
+        assert_eq!(nearest(blocked, &vec![]), SearchOutcome::StartBlocked);
+        assert_eq!(nearest(blocked, &outside), SearchOutcome::StartBlocked);
+    }
+}
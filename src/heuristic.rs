@@ -0,0 +1,72 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::movement::{DIAGONAL_COST, ORTHOGONAL_COST};
+use crate::Point;
+
+use std::f64::consts::SQRT_2;
+
+/// Estimate is implemented by anything that can estimate the cost of moving between two Points.
+/// Estimates are in the same units as movement costs, so one straight step is `ORTHOGONAL_COST`
+pub trait Estimate {
+    fn estimate(&self, from: &Point, to: &Point) -> i32;
+}
+
+///
+/// Heuristic selects a built in distance estimate
+///
+/// Manhattan -> dx + dy, admissible for 4-way movement
+///
+/// Euclidean -> Straight line distance, admissible for any movement
+///
+/// Octile -> Straight steps plus diagonal steps, admissible for 8-way movement
+///
+/// Chebyshev -> max(dx, dy), admissible for any movement but less informed than Octile
+///
+/// Zero -> Always 0, which turns the search into Dijkstra's algorithm
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Heuristic {
+    Manhattan,
+    Euclidean,
+    Octile,
+    Chebyshev,
+    Zero,
+}
+
+impl Estimate for Heuristic {
+    fn estimate(&self, from: &Point, to: &Point) -> i32 {
+        let dx = (from.x - to.x).abs();
+        let dy = (from.y - to.y).abs();
+        match self {
+            Heuristic::Manhattan => ORTHOGONAL_COST * (dx + dy),
+            // Scaled so a pure diagonal costs DIAGONAL_COST per step, since 14 rounds 10 * √2 down
+            Heuristic::Euclidean => {
+                (DIAGONAL_COST as f64 / SQRT_2 * ((dx * dx + dy * dy) as f64).sqrt()) as i32
+            }
+            Heuristic::Octile => {
+                ORTHOGONAL_COST * dx.max(dy) + (DIAGONAL_COST - ORTHOGONAL_COST) * dx.min(dy)
+            }
+            Heuristic::Chebyshev => ORTHOGONAL_COST * dx.max(dy),
+            Heuristic::Zero => 0,
+        }
+    }
+}
+
+/// Weighted scales another estimate. Weights above 1 trade path quality for fewer expanded Nodes
+pub struct Weighted<'a, E: Estimate + ?Sized> {
+    pub estimate: &'a E,
+    pub weight: f32,
+}
+
+impl<E: Estimate + ?Sized> Estimate for Weighted<'_, E> {
+    fn estimate(&self, from: &Point, to: &Point) -> i32 {
+        let h = self.estimate.estimate(from, to);
+        if self.weight == 1.0 {
+            h
+        } else {
+            (h as f32 * self.weight) as i32
+        }
+    }
+}
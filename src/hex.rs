@@ -0,0 +1,428 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use std::fmt::Display;
+
+use crate::graph::{self, Goal, Graph, GraphPath};
+use crate::grid::Grid;
+use crate::movement::ORTHOGONAL_COST;
+use crate::{Point, SearchOutcome};
+
+/// Hex represents a q,r axial coordinate on a hex grid. The third cube coordinate is `s = -q - r`
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Copy)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+impl Display for Hex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[ q: {}, r: {} ]", &self.q, &self.r)
+    }
+}
+
+/// The six axial directions, starting east and turning counterclockwise
+const HEX_DIRS: [Hex; 6] = [
+    Hex { q: 1, r: 0 },
+    Hex { q: 1, r: -1 },
+    Hex { q: 0, r: -1 },
+    Hex { q: -1, r: 0 },
+    Hex { q: -1, r: 1 },
+    Hex { q: 0, r: 1 },
+];
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Hex {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    /// The third cube coordinate
+    pub fn s(&self) -> i32 {
+        -self.q - self.r
+    }
+
+    /// Number of steps between two hexes
+    pub fn distance(&self, other: &Hex) -> i32 {
+        ((self.q - other.q).abs() + (self.r - other.r).abs() + (self.s() - other.s()).abs()) / 2
+    }
+
+    /// The column and row of this hex in offset coordinates. Pointy grids shift odd rows right,
+    /// flat grids shift odd columns down
+    pub fn to_offset(&self, orientation: HexOrientation) -> Point {
+        match orientation {
+            HexOrientation::Pointy => Point {
+                x: self.q + (self.r - (self.r & 1)) / 2,
+                y: self.r,
+            },
+            HexOrientation::Flat => Point {
+                x: self.q,
+                y: self.r + (self.q - (self.q & 1)) / 2,
+            },
+        }
+    }
+
+    /// The hex at a column and row in offset coordinates, the inverse of `to_offset`
+    pub fn from_offset(offset: &Point, orientation: HexOrientation) -> Hex {
+        match orientation {
+            HexOrientation::Pointy => Hex {
+                q: offset.x - (offset.y - (offset.y & 1)) / 2,
+                r: offset.y,
+            },
+            HexOrientation::Flat => Hex {
+                q: offset.x,
+                r: offset.y - (offset.x - (offset.x & 1)) / 2,
+            },
+        }
+    }
+}
+
+impl Hex {
+    /// Add another hex to the current hex and return a new hex
+    pub fn add(&self, other_hex: &Self) -> Hex {
+        Hex {
+            q: self.q + other_hex.q,
+            r: self.r + other_hex.r,
+        }
+    }
+}
+
+///
+/// HexOrientation selects how hexes are laid out and stored
+///
+/// Pointy -> Hexes have a point at the top and form horizontal rows, stored with odd rows shifted right
+///
+/// Flat -> Hexes have a flat top and form vertical columns, stored with odd columns shifted down
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum HexOrientation {
+    #[default]
+    Pointy,
+    Flat,
+}
+
+///
+/// A HexGrid is a rectangular map of hexes, stored in offset coordinates
+///
+/// grid: `Grid` -> The walls and cell costs, indexed by the offset coordinates of each hex
+///
+/// orientation: `HexOrientation` -> How hexes map to offset coordinates
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct HexGrid {
+    grid: Grid,
+    orientation: HexOrientation,
+}
+
+/// HexPathResult is the outcome of a search on a HexGrid, with the same meaning as `PathResult`
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Debug, Clone)]
+pub struct HexPathResult {
+    pub outcome: SearchOutcome,
+    pub path: Vec<Hex>,
+    pub cost: i32,
+    pub expanded: usize,
+}
+
+impl From<GraphPath<Hex>> for HexPathResult {
+    fn from(graph_path: GraphPath<Hex>) -> Self {
+        Self {
+            outcome: graph_path.outcome,
+            path: graph_path.path,
+            cost: graph_path.cost,
+            expanded: graph_path.expanded,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl HexGrid {
+    /// Create an empty hex grid with width columns and height rows in offset coordinates
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(width: usize, height: usize, orientation: HexOrientation) -> Self {
+        Self {
+            grid: Grid::new(width, height),
+            orientation,
+        }
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn height(&self) -> usize {
+        self.grid.height()
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn orientation(&self) -> HexOrientation {
+        self.orientation
+    }
+
+    /// Whether hex lies inside the grid
+    pub fn in_bounds(&self, hex: &Hex) -> bool {
+        let offset = hex.to_offset(self.orientation);
+        self.grid.in_bounds(offset.x, offset.y)
+    }
+
+    /// Mark hex as a wall. Does nothing if hex is outside the grid
+    pub fn set_wall(&mut self, hex: &Hex) {
+        let offset = hex.to_offset(self.orientation);
+        self.grid.set_wall(offset.x, offset.y);
+    }
+
+    /// Mark hex as open. Does nothing if hex is outside the grid
+    pub fn clear_wall(&mut self, hex: &Hex) {
+        let offset = hex.to_offset(self.orientation);
+        self.grid.clear_wall(offset.x, offset.y);
+    }
+
+    /// Whether hex is inside the grid, not a wall and not `IMPASSABLE`
+    pub fn is_walkable(&self, hex: &Hex) -> bool {
+        let offset = hex.to_offset(self.orientation);
+        self.grid.is_walkable(offset.x, offset.y)
+    }
+
+    /// Set the cost multiplier for entering hex. Does nothing if hex is outside the grid
+    pub fn set_cost(&mut self, hex: &Hex, cost: u32) {
+        let offset = hex.to_offset(self.orientation);
+        self.grid.set_cost(offset.x, offset.y, cost);
+    }
+
+    /// The hexes next to hex that can be moved to
+    pub fn neighbors(&self, hex: &Hex) -> Vec<Hex> {
+        self.successors(*hex)
+            .map(|(neighbor, _)| neighbor)
+            .collect()
+    }
+
+    /// Search for the shortest path from start to end
+    pub fn find_path(&self, start: Hex, end: Hex) -> HexPathResult {
+        let outcome = if !self.in_bounds(&start) || !self.in_bounds(&end) {
+            Some(SearchOutcome::OutOfBounds)
+        } else if !self.is_walkable(&start) {
+            Some(SearchOutcome::StartBlocked)
+        } else if !self.is_walkable(&end) {
+            Some(SearchOutcome::GoalBlocked)
+        } else {
+            None
+        };
+        if let Some(outcome) = outcome {
+            return GraphPath {
+                outcome,
+                path: Vec::new(),
+                cost: 0,
+                expanded: 0,
+            }
+            .into();
+        }
+
+        graph::search(self, start, &HexGoal { end }).into()
+    }
+}
+
+impl Graph for HexGrid {
+    type Node = Hex;
+
+    fn node_count(&self) -> usize {
+        self.grid.len()
+    }
+
+    fn index(&self, node: Hex) -> usize {
+        self.grid.index(&node.to_offset(self.orientation))
+    }
+
+    fn node(&self, index: usize) -> Hex {
+        Hex::from_offset(&self.grid.point(index), self.orientation)
+    }
+
+    fn successors(&self, node: Hex) -> impl Iterator<Item = (Hex, i32)> {
+        HEX_DIRS
+            .iter()
+            .map(move |dir| node.add(dir))
+            .filter(move |next| self.is_walkable(next))
+            .map(move |next| (next, self.step_cost(&next)))
+    }
+
+    /// A step costs the cost of the hex entered, which differs when reversed
+    fn predecessors(&self, node: Hex) -> impl Iterator<Item = (Hex, i32)> {
+        let cost = self.step_cost(&node);
+        HEX_DIRS
+            .iter()
+            .map(move |dir| node.add(dir))
+            .filter(move |previous| self.is_walkable(previous))
+            .map(move |previous| (previous, cost))
+    }
+}
+
+impl HexGrid {
+    fn step_cost(&self, to: &Hex) -> i32 {
+        let offset = to.to_offset(self.orientation);
+        let cell_cost = self.grid.cost(offset.x, offset.y);
+        ORTHOGONAL_COST.saturating_mul(i32::try_from(cell_cost).unwrap_or(i32::MAX))
+    }
+}
+
+/// A HexGoal ends a hex search at a single end hex, estimating with the hex distance
+pub struct HexGoal {
+    pub end: Hex,
+}
+
+impl Goal<Hex> for HexGoal {
+    fn is_goal(&self, node: Hex) -> bool {
+        node == self.end
+    }
+
+    fn estimate(&self, node: Hex) -> i32 {
+        ORTHOGONAL_COST * node.distance(&self.end)
+    }
+}
+
+/// Search a hex grid of the given size for the shortest path from start to end.
+/// Prefer a `HexGrid` when running many searches on the same map
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn hex_a_star(
+    width: usize,
+    height: usize,
+    orientation: HexOrientation,
+    start: Hex,
+    end: Hex,
+    walls: Vec<Hex>,
+) -> HexPathResult {
+    let mut grid = HexGrid::new(width, height, orientation);
+    for wall in walls {
+        grid.set_wall(&wall);
+    }
+    grid.find_path(start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Rng;
+    use std::collections::VecDeque;
+
+    const ORIENTATIONS: [HexOrientation; 2] = [HexOrientation::Pointy, HexOrientation::Flat];
+
+    /// Number of steps from start to end, by a breadth first search over `neighbors`
+    fn bfs(grid: &HexGrid, start: Hex, end: Hex) -> Option<i32> {
+        let mut steps = vec![None; grid.node_count()];
+        let mut open = VecDeque::from([start]);
+        steps[grid.index(start)] = Some(0);
+        while let Some(hex) = open.pop_front() {
+            let step = steps[grid.index(hex)]?;
+            if hex == end {
+                return Some(step);
+            }
+            for neighbor in grid.neighbors(&hex) {
+                let seen = &mut steps[grid.index(neighbor)];
+                if seen.is_none() {
+                    *seen = Some(step + 1);
+                    open.push_back(neighbor);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn offsets_round_trip() {
+        for orientation in ORIENTATIONS {
+            for y in -9..10 {
+                for x in -9..10 {
+                    let offset = Point { x, y };
+                    let hex = Hex::from_offset(&offset, orientation);
+                    assert_eq!(hex.to_offset(orientation), offset, "{orientation:?}");
+                    assert_eq!(
+                        Hex::from_offset(&hex.to_offset(orientation), orientation),
+                        hex
+                    );
+
+                    let axial = Hex::new(x, y);
+                    let back = Hex::from_offset(&axial.to_offset(orientation), orientation);
+                    assert_eq!(back, axial, "{orientation:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn odd_rows_and_columns_are_shifted() {
+        for y in -4..5 {
+            for x in -4..5 {
+                let odd = |n: i32| n & 1 == 1;
+                let pointy = if odd(y) {
+                    [(1, 0), (1, -1), (0, -1), (-1, 0), (0, 1), (1, 1)]
+                } else {
+                    [(1, 0), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1)]
+                };
+                let flat = if odd(x) {
+                    [(1, 0), (1, 1), (0, -1), (-1, 0), (-1, 1), (0, 1)]
+                } else {
+                    [(1, -1), (1, 0), (0, -1), (-1, -1), (-1, 0), (0, 1)]
+                };
+                for (orientation, steps) in [
+                    (HexOrientation::Pointy, pointy),
+                    (HexOrientation::Flat, flat),
+                ] {
+                    let hex = Hex::from_offset(&Point { x, y }, orientation);
+                    let mut neighbors: Vec<(i32, i32)> = HEX_DIRS
+                        .iter()
+                        .map(|dir| hex.add(dir).to_offset(orientation))
+                        .map(|offset| (offset.x - x, offset.y - y))
+                        .collect();
+                    let mut expected = steps.to_vec();
+                    neighbors.sort();
+                    expected.sort();
+                    assert_eq!(neighbors, expected, "{orientation:?} at {x},{y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_breadth_first_search() {
+        let mut rng = Rng::new(13);
+        for _ in 0..300 {
+            for orientation in ORIENTATIONS {
+                let mut grid = HexGrid::new(12, 10, orientation);
+                let hex_at = |rng: &mut Rng| {
+                    let offset = Point {
+                        x: rng.range(0, 12),
+                        y: rng.range(0, 10),
+                    };
+                    Hex::from_offset(&offset, orientation)
+                };
+                for _ in 0..rng.range(0, 50) {
+                    let wall = hex_at(&mut rng);
+                    grid.set_wall(&wall);
+                }
+                let (start, end) = (hex_at(&mut rng), hex_at(&mut rng));
+                if !grid.is_walkable(&start) || !grid.is_walkable(&end) {
+                    continue;
+                }
+
+                let result = grid.find_path(start, end);
+                match bfs(&grid, start, end) {
+                    Some(steps) => {
+                        assert_eq!(result.outcome, SearchOutcome::Found, "{orientation:?}");
+                        assert_eq!(result.cost, steps * ORTHOGONAL_COST);
+                        assert_eq!(result.path.first(), Some(&start));
+                        assert_eq!(result.path.last(), Some(&end));
+                        for pair in result.path.windows(2) {
+                            assert_eq!(pair[0].distance(&pair[1]), 1);
+                            assert!(grid.is_walkable(&pair[1]));
+                        }
+                    }
+                    None => assert_eq!(result.outcome, SearchOutcome::Unreachable),
+                }
+            }
+        }
+    }
+}
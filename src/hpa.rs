@@ -0,0 +1,787 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::graph::{self, Goal, Graph};
+use crate::grid::{Grid, GridGraph, PointGoal};
+use crate::heuristic::{Estimate, Heuristic};
+use crate::movement::{self, Dirs, Movement};
+use crate::search::{Priority, SearchState};
+use crate::{PathResult, Point, SearchOutcome};
+
+/// Entrances at least this many cells wide get a transition at each end instead of one in the middle
+const WIDE_ENTRANCE: usize = 6;
+
+/// The most expensive step a flood keeps a bucket for each cost up to, beyond which it uses a heap
+const MAX_BUCKETS: usize = 256;
+
+///
+/// A Hierarchy answers path queries on large grids with HPA*, Hierarchical Path-Finding A*
+///
+/// The grid is split into square clusters. Wherever two neighboring clusters share open border cells,
+/// transitions are placed across the border, and the cells on either side become entrances.
+/// Entrances are linked to the other entrances of their cluster by the cost of the cheapest path
+/// that stays inside it, and to the entrance across each of their transitions by a single step.
+/// A query searches this small graph of entrances, then fills in each link with a search bounded to one cluster
+///
+/// Paths are near optimal, since they can only cross between clusters at transitions.
+/// Editing a cell rebuilds only its cluster and the eight around it
+///
+/// grid: `Grid` -> A copy of the map, changed through the Hierarchy so the clusters stay up to date
+///
+/// movement: `Movement` -> Which neighbors of a cell can be moved to
+///
+/// cluster_size: `usize` -> The width and height of each cluster in cells. Clusters on the right and bottom edges may be smaller
+///
+/// nodes: `Vec<Option<Point>>` -> The entrance cell in each node slot, or None for a slot that is free for reuse
+///
+/// slots: `HashMap<usize, usize>` -> The node slot of each entrance, by the index of its cell
+///
+/// edges: `Vec<Vec<(usize, i32)>>` -> The node slots each node links to, with the cost of each link
+///
+/// entrances: `Vec<Vec<usize>>` -> The node slots of the entrances of each cluster
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone)]
+pub struct Hierarchy {
+    grid: Grid,
+    movement: Movement,
+    cluster_size: usize,
+    columns: usize,
+    rows: usize,
+    nodes: Vec<Option<Point>>,
+    free: Vec<usize>,
+    slots: HashMap<usize, usize>,
+    edges: Vec<Vec<(usize, i32)>>,
+    entrances: Vec<Vec<usize>>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Hierarchy {
+    /// Split a copy of grid into clusters of cluster_size by cluster_size cells and link their entrances
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(grid: &Grid, cluster_size: usize, movement: Movement) -> Self {
+        let cluster_size = cluster_size.max(1);
+        let columns = grid.width().div_ceil(cluster_size);
+        let rows = grid.height().div_ceil(cluster_size);
+        let mut hierarchy = Self {
+            grid: grid.clone(),
+            movement,
+            cluster_size,
+            columns,
+            rows,
+            nodes: Vec::new(),
+            free: Vec::new(),
+            slots: HashMap::new(),
+            edges: Vec::new(),
+            entrances: vec![Vec::new(); columns * rows],
+        };
+        let clusters: Vec<usize> = (0..columns * rows).collect();
+        hierarchy.rebuild(&clusters);
+        hierarchy
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn cluster_size(&self) -> usize {
+        self.cluster_size
+    }
+
+    /// Number of entrances linked between clusters
+    pub fn entrance_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Mark x,y as a wall and rebuild the clusters around it. Does nothing if x,y is outside the grid
+    pub fn set_wall(&mut self, x: i32, y: i32) {
+        if self.grid.in_bounds(x, y) {
+            self.grid.set_wall(x, y);
+            self.rebuild_around(x, y);
+        }
+    }
+
+    /// Mark x,y as open and rebuild the clusters around it. Does nothing if x,y is outside the grid
+    pub fn clear_wall(&mut self, x: i32, y: i32) {
+        if self.grid.in_bounds(x, y) {
+            self.grid.clear_wall(x, y);
+            self.rebuild_around(x, y);
+        }
+    }
+
+    /// Set the cost multiplier for entering x,y and rebuild the clusters around it. Does nothing if x,y is outside the grid
+    pub fn set_cost(&mut self, x: i32, y: i32, cost: u32) {
+        if self.grid.in_bounds(x, y) {
+            self.grid.set_cost(x, y, cost);
+            self.rebuild_around(x, y);
+        }
+    }
+
+    /// Search for a near optimal path from start to end through the entrances of the clusters
+    pub fn find_path(&self, start: Point, end: Point) -> PathResult {
+        if let Some(outcome) = self.grid.check_endpoints(&start, &end) {
+            return PathResult::failed(outcome, 0);
+        }
+
+        // Link start and end to the entrances of their own clusters, and to each other if they share one
+        let start_cluster = self.cluster_of(&start);
+        let end_cluster = self.cluster_of(&end);
+        let start_graph = self.cluster_graph(start_cluster);
+        let mut targets = self.entrance_cells(start_cluster);
+        if start_cluster == end_cluster {
+            targets.push(end);
+        }
+        let from_start = Moves::new(&start_graph, false).flood(&start_graph, start, &targets);
+        let end_graph = self.cluster_graph(end_cluster);
+        let to_end =
+            Moves::new(&end_graph, true).flood(&end_graph, end, &self.entrance_cells(end_cluster));
+
+        let start_slot = self.nodes.len();
+        let end_slot = start_slot + 1;
+        let mut start_edges = self.entrance_costs(start_cluster, &start_graph, &from_start);
+        if start_cluster == end_cluster {
+            let cost = from_start[start_graph.index(end)];
+            if cost != i32::MAX {
+                start_edges.push((end_slot, cost));
+            }
+        }
+        let end_edges = self
+            .entrance_costs(end_cluster, &end_graph, &to_end)
+            .into_iter()
+            .collect();
+
+        let graph = Entrances {
+            hierarchy: self,
+            start,
+            end,
+            start_edges,
+            end_edges,
+        };
+        let heuristic = self.movement.default_heuristic();
+        let goal = EntranceGoal {
+            graph: &graph,
+            heuristic,
+        };
+        let mut state = SearchState::new(&graph, start_slot, &goal, Priority::GPlusH);
+        while !state.step(&graph, &goal) {}
+        let abstract_path = state.path(&graph);
+        if abstract_path.outcome != SearchOutcome::Found {
+            return PathResult::failed(abstract_path.outcome, abstract_path.expanded);
+        }
+
+        // Fill in each link, stepping straight across transitions and searching inside clusters
+        let mut expanded = abstract_path.expanded;
+        let mut path = vec![start];
+        for pair in abstract_path.path.windows(2) {
+            let (from, to) = (graph.cell(pair[0]), graph.cell(pair[1]));
+            let cluster = self.cluster_of(&from);
+            if cluster != self.cluster_of(&to) {
+                path.push(to);
+                continue;
+            }
+            let refined = graph::search(
+                &self.cluster_graph(cluster),
+                from,
+                &PointGoal {
+                    end: to,
+                    estimate: &heuristic,
+                },
+            );
+            expanded += refined.expanded;
+            path.extend(refined.path.into_iter().skip(1));
+        }
+
+        PathResult {
+            outcome: SearchOutcome::Found,
+            path,
+            cost: abstract_path.cost,
+            expanded,
+            partial: false,
+        }
+    }
+}
+
+impl Hierarchy {
+    /// The cluster containing pos
+    fn cluster_of(&self, pos: &Point) -> usize {
+        let column = pos.x as usize / self.cluster_size;
+        let row = pos.y as usize / self.cluster_size;
+        row * self.columns + column
+    }
+
+    /// The grid as a Graph bounded to the cells of cluster
+    fn cluster_graph(&self, cluster: usize) -> ClusterGraph<'_> {
+        let (column, row) = (cluster % self.columns, cluster / self.columns);
+        let (left, top) = (column * self.cluster_size, row * self.cluster_size);
+        ClusterGraph {
+            graph: self.grid.graph(self.movement),
+            left: left as i32,
+            top: top as i32,
+            width: self.cluster_size.min(self.grid.width() - left),
+            height: self.cluster_size.min(self.grid.height() - top),
+        }
+    }
+
+    /// The cells of the entrances of cluster
+    fn entrance_cells(&self, cluster: usize) -> Vec<Point> {
+        self.entrances[cluster]
+            .iter()
+            .filter_map(|slot| self.nodes[*slot])
+            .collect()
+    }
+
+    /// The costs in a flood of cluster to each of its entrances that the flood reached
+    fn entrance_costs(
+        &self,
+        cluster: usize,
+        graph: &ClusterGraph,
+        costs: &[i32],
+    ) -> Vec<(usize, i32)> {
+        self.entrances[cluster]
+            .iter()
+            .filter_map(|slot| {
+                let cell = self.nodes[*slot]?;
+                let cost = costs[graph.index(cell)];
+                (cost != i32::MAX).then_some((*slot, cost))
+            })
+            .collect()
+    }
+
+    /// Rebuild the cluster containing x,y and the eight around it, whose entrances share its borders and corners
+    fn rebuild_around(&mut self, x: i32, y: i32) {
+        let cluster = self.cluster_of(&Point { x, y });
+        let (column, row) = (cluster % self.columns, cluster / self.columns);
+        let mut clusters = Vec::new();
+        for row in row.saturating_sub(1)..=(row + 1).min(self.rows - 1) {
+            for column in column.saturating_sub(1)..=(column + 1).min(self.columns - 1) {
+                clusters.push(row * self.columns + column);
+            }
+        }
+        self.rebuild(&clusters);
+    }
+
+    /// Place the entrances of clusters again, keeping the node slots of entrances that did not move,
+    /// then link them. Entrances in other clusters keep their links, since their borders are unchanged
+    fn rebuild(&mut self, clusters: &[usize]) {
+        for &cluster in clusters {
+            // A corner cell can be an entrance on two borders
+            let mut cells: Vec<Point> = Vec::new();
+            for (inside, _) in self.transitions(cluster) {
+                if !cells.contains(&inside) {
+                    cells.push(inside);
+                }
+            }
+
+            for slot in std::mem::take(&mut self.entrances[cluster]) {
+                let Some(cell) = self.nodes[slot] else {
+                    continue;
+                };
+                if !cells.contains(&cell) {
+                    self.nodes[slot] = None;
+                    self.edges[slot].clear();
+                    self.slots.remove(&self.grid.index(&cell));
+                    self.free.push(slot);
+                }
+            }
+            self.entrances[cluster] = cells.into_iter().map(|cell| self.slot(cell)).collect();
+        }
+
+        for &cluster in clusters {
+            self.link(cluster);
+        }
+    }
+
+    /// The node slot of an entrance cell, taking a free slot if it has none
+    fn slot(&mut self, cell: Point) -> usize {
+        let index = self.grid.index(&cell);
+        if let Some(slot) = self.slots.get(&index) {
+            return *slot;
+        }
+        let slot = self.free.pop().unwrap_or_else(|| {
+            self.nodes.push(None);
+            self.edges.push(Vec::new());
+            self.nodes.len() - 1
+        });
+        self.nodes[slot] = Some(cell);
+        self.slots.insert(index, slot);
+        slot
+    }
+
+    /// Link each entrance of cluster to the others it can reach inside the cluster, and across its transitions
+    fn link(&mut self, cluster: usize) {
+        let graph = self.cluster_graph(cluster);
+        let transitions = self.transitions(cluster);
+        let cells = self.entrance_cells(cluster);
+        let moves = Moves::new(&graph, false);
+        let mut links = Vec::new();
+        for &slot in &self.entrances[cluster] {
+            let Some(cell) = self.nodes[slot] else {
+                continue;
+            };
+            let costs = moves.flood(&graph, cell, &cells);
+            let mut edges: Vec<(usize, i32)> = self
+                .entrance_costs(cluster, &graph, &costs)
+                .into_iter()
+                .filter(|(other, _)| *other != slot)
+                .collect();
+            for (_, outside) in transitions.iter().filter(|(inside, _)| *inside == cell) {
+                let cost = i32::try_from(self.grid.cost(outside.x, outside.y)).unwrap_or(i32::MAX);
+                edges.push((
+                    self.slots[&self.grid.index(outside)],
+                    movement::step_cost(&cell, outside).saturating_mul(cost),
+                ));
+            }
+            links.push((slot, edges));
+        }
+        for (slot, edges) in links {
+            self.edges[slot] = edges;
+        }
+    }
+
+    /// Every transition out of cluster, as the open cell on its side of the border and the open cell across it
+    fn transitions(&self, cluster: usize) -> Vec<(Point, Point)> {
+        let graph = self.cluster_graph(cluster);
+        let (column, row) = (cluster % self.columns, cluster / self.columns);
+        let (left, top) = (graph.left, graph.top);
+        let (right, bottom) = (left + graph.width as i32 - 1, top + graph.height as i32 - 1);
+        let step = |pos: Point, dx: i32, dy: i32| {
+            (
+                pos,
+                Point {
+                    x: pos.x + dx,
+                    y: pos.y + dy,
+                },
+            )
+        };
+
+        let mut transitions = Vec::new();
+        if row > 0 {
+            let border = (left..=right).map(|x| step(Point { x, y: top }, 0, -1));
+            transitions.extend(self.crossings(border));
+        }
+        if column > 0 {
+            let border = (top..=bottom).map(|y| step(Point { x: left, y }, -1, 0));
+            transitions.extend(self.crossings(border));
+        }
+        if column + 1 < self.columns {
+            let border = (top..=bottom).map(|y| step(Point { x: right, y }, 1, 0));
+            transitions.extend(self.crossings(border));
+        }
+        if row + 1 < self.rows {
+            let border = (left..=right).map(|x| step(Point { x, y: bottom }, 0, 1));
+            transitions.extend(self.crossings(border));
+        }
+
+        // Squeezing diagonally between two walls is the only way through some gaps when corners can be cut
+        if self.movement == Movement::EightWay {
+            let walkable = |x, y| self.grid.is_walkable(x, y);
+            let perimeter = (top..=bottom)
+                .flat_map(|y| (left..=right).map(move |x| Point { x, y }))
+                .filter(|pos| pos.x == left || pos.x == right || pos.y == top || pos.y == bottom);
+            for inside in perimeter {
+                for (dx, dy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                    let (inside, outside) = step(inside, dx, dy);
+                    let squeeze =
+                        !walkable(inside.x + dx, inside.y) && !walkable(inside.x, inside.y + dy);
+                    if !graph.contains(&outside)
+                        && squeeze
+                        && walkable(inside.x, inside.y)
+                        && walkable(outside.x, outside.y)
+                    {
+                        transitions.push((inside, outside));
+                    }
+                }
+            }
+        }
+        transitions
+    }
+
+    /// The transitions across one border, given every pair of cells facing each other along it in order.
+    /// Each run of open pairs is an entrance, crossed in the middle, or at both ends if it is wide
+    fn crossings(&self, border: impl Iterator<Item = (Point, Point)>) -> Vec<(Point, Point)> {
+        let mut runs: Vec<Vec<(Point, Point)>> = vec![Vec::new()];
+        for (inside, outside) in border {
+            let open = self.grid.is_walkable(inside.x, inside.y)
+                && self.grid.is_walkable(outside.x, outside.y);
+            match runs.last_mut() {
+                Some(run) if open => run.push((inside, outside)),
+                Some(run) if !run.is_empty() => runs.push(Vec::new()),
+                _ => {}
+            }
+        }
+
+        let mut crossings = Vec::new();
+        for run in runs.iter().filter(|run| !run.is_empty()) {
+            if run.len() >= WIDE_ENTRANCE {
+                crossings.extend([run[0], run[run.len() - 1]]);
+            } else {
+                crossings.push(run[run.len() / 2]);
+            }
+        }
+        crossings
+    }
+}
+
+///
+/// Moves lists every step inside one cluster by the index of the cell it leaves, so the floods from each
+/// of its entrances walk a table instead of checking the walls and movement rules of the grid again
+///
+/// starts: `Vec<usize>` -> Where the steps out of each cell begin in steps, with one extra entry for the end
+///
+/// steps: `Vec<(usize, i32)>` -> The index of the cell each step enters, with its cost
+///
+/// max_step: `i32` -> The cost of the most expensive step
+///
+struct Moves {
+    starts: Vec<usize>,
+    steps: Vec<(usize, i32)>,
+    max_step: i32,
+}
+
+impl Moves {
+    /// The steps inside the cluster of graph, or the steps taken backwards when reversed
+    fn new(graph: &ClusterGraph, reversed: bool) -> Self {
+        let (grid, movement) = (graph.graph.grid, graph.graph.movement);
+        let open: Vec<bool> = (0..graph.node_count())
+            .map(|index| {
+                let pos = graph.node(index);
+                grid.is_walkable(pos.x, pos.y)
+            })
+            .collect();
+        let inside = |x, y| {
+            let pos = Point { x, y };
+            graph.contains(&pos) && open[graph.index(pos)]
+        };
+
+        // Floods never reach a wall, so walls have no steps
+        let mut starts = Vec::with_capacity(graph.node_count() + 1);
+        let mut steps = Vec::new();
+        for (index, pos) in (0..graph.node_count()).map(|index| (index, graph.node(index))) {
+            starts.push(steps.len());
+            if !open[index] {
+                continue;
+            }
+            for dir in movement.dirs() {
+                if movement.allows(inside, &pos, dir) {
+                    let next = pos.add(&Dirs::get(dir));
+                    let entered = if reversed { pos } else { next };
+                    let cost = i32::try_from(grid.cost(entered.x, entered.y)).unwrap_or(i32::MAX);
+                    let step = movement::step_cost(&pos, &next).saturating_mul(cost);
+                    steps.push((graph.index(next), step));
+                }
+            }
+        }
+        starts.push(steps.len());
+        let max_step = steps.iter().map(|(_, step)| *step).max().unwrap_or(0);
+        Self {
+            starts,
+            steps,
+            max_step,
+        }
+    }
+
+    /// Cost from start to the cells of a cluster, flooding outwards until every one of targets, all cells of graph,
+    /// has been expanded. Only the costs of targets are final, and cells the flood cannot reach cost `i32::MAX`
+    fn flood(&self, graph: &ClusterGraph, start: Point, targets: &[Point]) -> Vec<i32> {
+        let mut is_target = vec![false; graph.node_count()];
+        let mut remaining = 0;
+        for target in targets {
+            let index = graph.index(*target);
+            if !is_target[index] {
+                is_target[index] = true;
+                remaining += 1;
+            }
+        }
+
+        let mut costs = vec![i32::MAX; graph.node_count()];
+        let mut open = Open::new(self.max_step);
+        costs[graph.index(start)] = 0;
+        open.push(0, graph.index(start));
+        while remaining > 0 {
+            let Some((cost, index)) = open.pop() else {
+                break;
+            };
+            if cost > costs[index] {
+                continue;
+            }
+            if is_target[index] {
+                is_target[index] = false;
+                remaining -= 1;
+            }
+            for &(next, step) in &self.steps[self.starts[index]..self.starts[index + 1]] {
+                let next_cost = cost.saturating_add(step);
+                if next_cost < costs[next] {
+                    costs[next] = next_cost;
+                    open.push(next_cost, next);
+                }
+            }
+        }
+        costs
+    }
+}
+
+/// The open list of a flood. Steps usually cost little, so it is a ring of buckets with one for each cost
+/// from the lowest open cost up to a step more, falling back to a heap when a step costs more than `MAX_BUCKETS`
+enum Open {
+    Buckets {
+        ring: Vec<Vec<usize>>,
+        cost: i32,
+        len: usize,
+    },
+    Heap(BinaryHeap<Reverse<(i32, usize)>>),
+}
+
+impl Open {
+    fn new(max_step: i32) -> Self {
+        match usize::try_from(max_step) {
+            Ok(max_step) if max_step < MAX_BUCKETS => Open::Buckets {
+                ring: vec![Vec::new(); max_step + 1],
+                cost: 0,
+                len: 0,
+            },
+            _ => Open::Heap(BinaryHeap::new()),
+        }
+    }
+
+    /// Add index at cost, which is never lower than the cost last popped
+    fn push(&mut self, cost: i32, index: usize) {
+        match self {
+            Open::Buckets { ring, len, .. } => {
+                let bucket = cost as usize % ring.len();
+                ring[bucket].push(index);
+                *len += 1;
+            }
+            Open::Heap(heap) => heap.push(Reverse((cost, index))),
+        }
+    }
+
+    /// Take an index with the lowest cost, and that cost
+    fn pop(&mut self) -> Option<(i32, usize)> {
+        match self {
+            Open::Buckets { ring, cost, len } => {
+                while *len > 0 {
+                    let bucket = *cost as usize % ring.len();
+                    if let Some(index) = ring[bucket].pop() {
+                        *len -= 1;
+                        return Some((*cost, index));
+                    }
+                    *cost += 1;
+                }
+                None
+            }
+            Open::Heap(heap) => heap.pop().map(|Reverse(entry)| entry),
+        }
+    }
+}
+
+/// A ClusterGraph walks the cells of one cluster of a Grid, indexed from its top left cell
+struct ClusterGraph<'a> {
+    graph: GridGraph<'a>,
+    left: i32,
+    top: i32,
+    width: usize,
+    height: usize,
+}
+
+impl ClusterGraph<'_> {
+    fn contains(&self, pos: &Point) -> bool {
+        let (x, y) = (pos.x - self.left, pos.y - self.top);
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+}
+
+impl Graph for ClusterGraph<'_> {
+    type Node = Point;
+
+    fn node_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn index(&self, node: Point) -> usize {
+        (node.y - self.top) as usize * self.width + (node.x - self.left) as usize
+    }
+
+    fn node(&self, index: usize) -> Point {
+        Point {
+            x: self.left + (index % self.width) as i32,
+            y: self.top + (index / self.width) as i32,
+        }
+    }
+
+    fn successors(&self, node: Point) -> impl Iterator<Item = (Point, i32)> {
+        self.graph
+            .successors(node)
+            .filter(move |(next, _)| self.contains(next))
+    }
+
+    fn predecessors(&self, node: Point) -> impl Iterator<Item = (Point, i32)> {
+        self.graph
+            .predecessors(node)
+            .filter(move |(previous, _)| self.contains(previous))
+    }
+}
+
+/// The entrances of a Hierarchy as a Graph, with start and end of one query in the two slots after them
+struct Entrances<'a> {
+    hierarchy: &'a Hierarchy,
+    start: Point,
+    end: Point,
+    start_edges: Vec<(usize, i32)>,
+    end_edges: HashMap<usize, i32>,
+}
+
+impl Entrances<'_> {
+    fn start_slot(&self) -> usize {
+        self.hierarchy.nodes.len()
+    }
+
+    fn end_slot(&self) -> usize {
+        self.hierarchy.nodes.len() + 1
+    }
+
+    /// The cell of a node slot
+    fn cell(&self, slot: usize) -> Point {
+        if slot == self.start_slot() {
+            self.start
+        } else if slot == self.end_slot() {
+            self.end
+        } else {
+            self.hierarchy.nodes[slot].unwrap_or(self.end)
+        }
+    }
+}
+
+impl Graph for Entrances<'_> {
+    type Node = usize;
+
+    fn node_count(&self) -> usize {
+        self.hierarchy.nodes.len() + 2
+    }
+
+    fn index(&self, node: usize) -> usize {
+        node
+    }
+
+    fn node(&self, index: usize) -> usize {
+        index
+    }
+
+    fn successors(&self, node: usize) -> impl Iterator<Item = (usize, i32)> {
+        let edges = if node == self.start_slot() {
+            &self.start_edges
+        } else {
+            self.hierarchy
+                .edges
+                .get(node)
+                .map_or(&[][..], Vec::as_slice)
+        };
+        let to_end = self
+            .end_edges
+            .get(&node)
+            .map(|cost| (self.end_slot(), *cost));
+        edges.iter().copied().chain(to_end)
+    }
+}
+
+/// An EntranceGoal ends a search of Entrances at the end slot, estimating with the distance between cells
+struct EntranceGoal<'a, 'b> {
+    graph: &'a Entrances<'b>,
+    heuristic: Heuristic,
+}
+
+impl Goal<usize> for EntranceGoal<'_, '_> {
+    fn is_goal(&self, node: usize) -> bool {
+        node == self.graph.end_slot()
+    }
+
+    fn estimate(&self, node: usize) -> i32 {
+        self.heuristic
+            .estimate(&self.graph.cell(node), &self.graph.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{dijkstra, random_grid, walk, Rng, MOVEMENTS};
+
+    /// Check result is a valid path from start to end no cheaper than the optimal one
+    fn check(grid: &Grid, movement: Movement, start: Point, end: Point, result: &PathResult) {
+        match dijkstra(grid, movement, start, end) {
+            Some(optimal) => {
+                assert_eq!(
+                    result.outcome,
+                    SearchOutcome::Found,
+                    "{movement:?} {start} to {end}"
+                );
+                assert_eq!(result.path.first(), Some(&start));
+                assert_eq!(result.path.last(), Some(&end));
+                assert_eq!(walk(grid, movement, &result.path), result.cost);
+                assert!(result.cost >= optimal);
+            }
+            None => assert_ne!(result.outcome, SearchOutcome::Found),
+        }
+    }
+
+    #[test]
+    fn finds_valid_paths() {
+        let mut rng = Rng::new(25);
+        for _ in 0..100 {
+            let (width, height) = (rng.range(1, 40) as usize, rng.range(1, 40) as usize);
+            let costs = rng.one_in(3);
+            let grid = random_grid(&mut rng, width, height, 4, costs);
+            let cluster_size = rng.range(2, 12) as usize;
+
+            for movement in MOVEMENTS {
+                let hierarchy = Hierarchy::new(&grid, cluster_size, movement);
+                for _ in 0..10 {
+                    let (start, end) = (rng.point(&grid), rng.point(&grid));
+                    if grid.check_endpoints(&start, &end).is_none() {
+                        check(
+                            &grid,
+                            movement,
+                            start,
+                            end,
+                            &hierarchy.find_path(start, end),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edits_match_a_fresh_build() {
+        let mut rng = Rng::new(52);
+        for movement in MOVEMENTS {
+            let mut grid = random_grid(&mut rng, 40, 30, 5, false);
+            let mut hierarchy = Hierarchy::new(&grid, 8, movement);
+            for _ in 0..150 {
+                let pos = rng.point(&grid);
+                if rng.one_in(2) {
+                    grid.set_wall(pos.x, pos.y);
+                    hierarchy.set_wall(pos.x, pos.y);
+                } else {
+                    grid.clear_wall(pos.x, pos.y);
+                    hierarchy.clear_wall(pos.x, pos.y);
+                }
+
+                let fresh = Hierarchy::new(&grid, 8, movement);
+                assert_eq!(hierarchy.entrance_count(), fresh.entrance_count());
+                for _ in 0..5 {
+                    let (start, end) = (rng.point(&grid), rng.point(&grid));
+                    let result = hierarchy.find_path(start, end);
+                    let expected = fresh.find_path(start, end);
+                    assert_eq!(
+                        result.outcome, expected.outcome,
+                        "{movement:?} {start} to {end}"
+                    );
+                    assert_eq!(result.cost, expected.cost, "{movement:?} {start} to {end}");
+                    if grid.check_endpoints(&start, &end).is_none() {
+                        check(&grid, movement, start, end, &result);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use crate::grid::Grid;
+use crate::movement::{Movement, DIAGONAL_COST, ORTHOGONAL_COST};
+use crate::Point;
+
+///
+/// JumpPoints finds the successors of a Node for Jump Point Search
+///
+/// Instead of stepping to each neighbor, the search jumps in a straight or diagonal line until it
+/// reaches a cell with a forced neighbor, a neighbor that can only be reached optimally through that cell.
+/// Only those jump points are put on the open list, which skips the many symmetric paths on open ground.
+/// Jumps assume every open cell costs the same, so they are only used on grids without a cost layer
+///
+/// The pruning rules follow the movement rules, so each Movement gets its own variant
+///
+pub(crate) struct JumpPoints<'a> {
+    pub(crate) grid: &'a Grid,
+    pub(crate) movement: Movement,
+    pub(crate) agent_size: usize,
+    pub(crate) end: Point,
+}
+
+impl JumpPoints<'_> {
+    /// Jump points reachable from pos, pruning directions that a parent at parent already covers
+    pub(crate) fn successors(&self, pos: Point, parent: Option<Point>) -> Vec<(Point, i32)> {
+        self.neighbors(pos, parent)
+            .into_iter()
+            .filter_map(|(dx, dy)| self.jump(pos.x + dx, pos.y + dy, dx, dy))
+            .map(|jump_point| (jump_point, jump_cost(&pos, &jump_point)))
+            .collect()
+    }
+
+    fn walkable(&self, x: i32, y: i32) -> bool {
+        self.grid.fits(x, y, self.agent_size)
+    }
+
+    /// Directions worth jumping in from pos, given the direction it was reached from
+    fn neighbors(&self, pos: Point, parent: Option<Point>) -> Vec<(i32, i32)> {
+        let Some(parent) = parent else {
+            return self
+                .movement
+                .dirs()
+                .filter(|dir| {
+                    self.movement
+                        .can_move(self.grid, &pos, dir, self.agent_size)
+                })
+                .map(|dir| {
+                    let step = crate::movement::Dirs::get(dir);
+                    (step.x, step.y)
+                })
+                .collect();
+        };
+
+        let (x, y) = (pos.x, pos.y);
+        let dx = (x - parent.x).signum();
+        let dy = (y - parent.y).signum();
+        let walkable = |x, y| self.walkable(x, y);
+        let mut dirs = Vec::new();
+
+        match self.movement {
+            Movement::FourWay => {
+                if dx != 0 {
+                    dirs.extend([(0, -1), (0, 1), (dx, 0)]);
+                } else {
+                    dirs.extend([(-1, 0), (1, 0), (0, dy)]);
+                }
+            }
+            Movement::EightWay => {
+                if dx != 0 && dy != 0 {
+                    dirs.extend([(0, dy), (dx, 0), (dx, dy)]);
+                    if !walkable(x - dx, y) {
+                        dirs.push((-dx, dy));
+                    }
+                    if !walkable(x, y - dy) {
+                        dirs.push((dx, -dy));
+                    }
+                } else if dx == 0 {
+                    dirs.push((0, dy));
+                    if !walkable(x + 1, y) {
+                        dirs.push((1, dy));
+                    }
+                    if !walkable(x - 1, y) {
+                        dirs.push((-1, dy));
+                    }
+                } else {
+                    dirs.push((dx, 0));
+                    if !walkable(x, y + 1) {
+                        dirs.push((dx, 1));
+                    }
+                    if !walkable(x, y - 1) {
+                        dirs.push((dx, -1));
+                    }
+                }
+            }
+            Movement::EightWayNoCornerCutting => {
+                if dx != 0 && dy != 0 {
+                    dirs.extend([(0, dy), (dx, 0)]);
+                    if walkable(x, y + dy) && walkable(x + dx, y) {
+                        dirs.push((dx, dy));
+                    }
+                } else if dx != 0 {
+                    let next = walkable(x + dx, y);
+                    let below = walkable(x, y + 1);
+                    let above = walkable(x, y - 1);
+                    if next {
+                        dirs.push((dx, 0));
+                        if below {
+                            dirs.push((dx, 1));
+                        }
+                        if above {
+                            dirs.push((dx, -1));
+                        }
+                    }
+                    dirs.extend([(0, 1), (0, -1)]);
+                } else {
+                    let next = walkable(x, y + dy);
+                    let right = walkable(x + 1, y);
+                    let left = walkable(x - 1, y);
+                    if next {
+                        dirs.push((0, dy));
+                        if right {
+                            dirs.push((1, dy));
+                        }
+                        if left {
+                            dirs.push((-1, dy));
+                        }
+                    }
+                    dirs.extend([(1, 0), (-1, 0)]);
+                }
+            }
+            Movement::EightWayOneCorner => {
+                if dx != 0 && dy != 0 {
+                    let vertical = walkable(x, y + dy);
+                    let horizontal = walkable(x + dx, y);
+                    dirs.extend([(0, dy), (dx, 0)]);
+                    if vertical || horizontal {
+                        dirs.push((dx, dy));
+                    }
+                    if !walkable(x - dx, y) && vertical {
+                        dirs.push((-dx, dy));
+                    }
+                    if !walkable(x, y - dy) && horizontal {
+                        dirs.push((dx, -dy));
+                    }
+                } else if dx == 0 {
+                    if walkable(x, y + dy) {
+                        dirs.push((0, dy));
+                        if !walkable(x + 1, y) {
+                            dirs.push((1, dy));
+                        }
+                        if !walkable(x - 1, y) {
+                            dirs.push((-1, dy));
+                        }
+                    }
+                } else if walkable(x + dx, y) {
+                    dirs.push((dx, 0));
+                    if !walkable(x, y + 1) {
+                        dirs.push((dx, 1));
+                    }
+                    if !walkable(x, y - 1) {
+                        dirs.push((dx, -1));
+                    }
+                }
+            }
+        }
+
+        // A diagonal is only a neighbor if the movement rules allow stepping onto it from pos
+        dirs.retain(|&(dx, dy)| dx == 0 || dy == 0 || self.can_step_diagonally(x, y, dx, dy));
+        dirs
+    }
+
+    fn can_step_diagonally(&self, x: i32, y: i32, dx: i32, dy: i32) -> bool {
+        let horizontal = self.walkable(x + dx, y);
+        let vertical = self.walkable(x, y + dy);
+        match self.movement {
+            Movement::FourWay => false,
+            Movement::EightWay => true,
+            Movement::EightWayNoCornerCutting => horizontal && vertical,
+            Movement::EightWayOneCorner => horizontal || vertical,
+        }
+    }
+
+    /// Walk from x,y in direction dx,dy, returning the first jump point reached.
+    /// Straight jumps are checked from diagonal ones (and horizontal from vertical for 4-way movement),
+    /// so the nesting is never more than two levels deep however far the jump goes
+    fn jump(&self, mut x: i32, mut y: i32, dx: i32, dy: i32) -> Option<Point> {
+        let walkable = |x, y| self.walkable(x, y);
+        loop {
+            if !walkable(x, y) {
+                return None;
+            }
+            if x == self.end.x && y == self.end.y {
+                return Some(self.end);
+            }
+
+            let forced = match self.movement {
+                Movement::FourWay => {
+                    if dx != 0 {
+                        (walkable(x, y - 1) && !walkable(x - dx, y - 1))
+                            || (walkable(x, y + 1) && !walkable(x - dx, y + 1))
+                    } else {
+                        (walkable(x - 1, y) && !walkable(x - 1, y - dy))
+                            || (walkable(x + 1, y) && !walkable(x + 1, y - dy))
+                            || self.jump(x + 1, y, 1, 0).is_some()
+                            || self.jump(x - 1, y, -1, 0).is_some()
+                    }
+                }
+                Movement::EightWay | Movement::EightWayOneCorner => {
+                    if dx != 0 && dy != 0 {
+                        (walkable(x - dx, y + dy) && !walkable(x - dx, y))
+                            || (walkable(x + dx, y - dy) && !walkable(x, y - dy))
+                    } else if dx != 0 {
+                        (walkable(x + dx, y + 1) && !walkable(x, y + 1))
+                            || (walkable(x + dx, y - 1) && !walkable(x, y - 1))
+                    } else {
+                        (walkable(x + 1, y + dy) && !walkable(x + 1, y))
+                            || (walkable(x - 1, y + dy) && !walkable(x - 1, y))
+                    }
+                }
+                Movement::EightWayNoCornerCutting => {
+                    if dx != 0 && dy != 0 {
+                        false
+                    } else if dx != 0 {
+                        (walkable(x, y - 1) && !walkable(x - dx, y - 1))
+                            || (walkable(x, y + 1) && !walkable(x - dx, y + 1))
+                    } else {
+                        (walkable(x - 1, y) && !walkable(x - 1, y - dy))
+                            || (walkable(x + 1, y) && !walkable(x + 1, y - dy))
+                    }
+                }
+            };
+            if forced {
+                return Some(Point { x, y });
+            }
+
+            // A diagonal jump stops wherever one of its straight components finds a jump point
+            if dx != 0
+                && dy != 0
+                && (self.jump(x + dx, y, dx, 0).is_some() || self.jump(x, y + dy, 0, dy).is_some())
+            {
+                return Some(Point { x, y });
+            }
+
+            if dx != 0 && dy != 0 && !self.can_step_diagonally(x, y, dx, dy) {
+                return None;
+            }
+            x += dx;
+            y += dy;
+        }
+    }
+}
+
+/// Cost of a straight or diagonal jump between two Points
+fn jump_cost(from: &Point, to: &Point) -> i32 {
+    let dx = (from.x - to.x).abs();
+    let dy = (from.y - to.y).abs();
+    ORTHOGONAL_COST * (dx.max(dy) - dx.min(dy)) + DIAGONAL_COST * dx.min(dy)
+}
+
+/// Fill in the cells between consecutive jump points, which always lie on a straight or diagonal line
+pub(crate) fn expand_jumps(jump_points: &[Point]) -> Vec<Point> {
+    let mut path: Vec<Point> = jump_points.iter().take(1).copied().collect();
+    for pair in jump_points.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        let step = Point {
+            x: (to.x - from.x).signum(),
+            y: (to.y - from.y).signum(),
+        };
+        let mut pos = from;
+        while pos != to {
+            pos = pos.add(&step);
+            path.push(pos);
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{random_grid, walk, Rng, MOVEMENTS};
+    use crate::{AStarSearch, Algorithm, Grid, Movement, Point, SearchOptions, SearchOutcome};
+
+    fn options(algorithm: Algorithm, movement: Movement) -> SearchOptions {
+        SearchOptions {
+            algorithm,
+            movement,
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn same_cost_as_a_star() {
+        let mut rng = Rng::new(14);
+        for _ in 0..2000 {
+            let (width, height) = (rng.range(1, 24) as usize, rng.range(1, 24) as usize);
+            let grid = random_grid(&mut rng, width, height, 4, false);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+
+            for movement in MOVEMENTS {
+                let a_star = grid.find_path_with(start, end, &options(Algorithm::AStar, movement));
+                let jps = grid.find_path_with(start, end, &options(Algorithm::JumpPoint, movement));
+                assert_eq!(
+                    jps.outcome, a_star.outcome,
+                    "{movement:?} from {start} to {end}"
+                );
+                assert_eq!(jps.cost, a_star.cost, "{movement:?} from {start} to {end}");
+                if jps.outcome == SearchOutcome::Found {
+                    assert_eq!(jps.path.first(), Some(&start));
+                    assert_eq!(jps.path.last(), Some(&end));
+                    assert_eq!(walk(&grid, movement, &jps.path), jps.cost);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn expands_fewer_nodes_on_open_ground() {
+        let grid = Grid::with_walls(64, 64, vec![Point { x: 30, y: 20 }, Point { x: 40, y: 50 }]);
+        let (start, end) = (Point { x: 0, y: 0 }, Point { x: 63, y: 45 });
+        for movement in MOVEMENTS {
+            let a_star = grid.find_path_with(start, end, &options(Algorithm::AStar, movement));
+            let jps = grid.find_path_with(start, end, &options(Algorithm::JumpPoint, movement));
+            assert_eq!(jps.cost, a_star.cost);
+            assert!(
+                jps.expanded < a_star.expanded,
+                "{movement:?} expanded {} against {}",
+                jps.expanded,
+                a_star.expanded
+            );
+        }
+    }
+
+    #[test]
+    fn stepper_fills_in_the_path() {
+        let mut rng = Rng::new(41);
+        for _ in 0..500 {
+            let grid = random_grid(&mut rng, 16, 16, 4, false);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+
+            for movement in MOVEMENTS {
+                let options = options(Algorithm::JumpPoint, movement);
+                let expected = grid.find_path_with(start, end, &options);
+                let mut search = AStarSearch::new(&grid, start, end, Some(options));
+                while !search.step_n(3) {}
+                let stepped = search.result().unwrap();
+                let run = AStarSearch::new(&grid, start, end, Some(options)).run();
+
+                for result in [stepped, run] {
+                    assert_eq!(result.outcome, expected.outcome);
+                    assert_eq!(result.cost, expected.cost);
+                    assert_eq!(result.path, expected.path);
+                    assert_eq!(walk(&grid, movement, &result.path), result.cost);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn partial_paths_match_a_star() {
+        let wall = (0..10).map(|y| Point { x: 6, y }).collect();
+        let grid = Grid::with_walls(10, 10, wall);
+        let (start, end) = (Point { x: 0, y: 0 }, Point { x: 9, y: 9 });
+        for movement in MOVEMENTS {
+            let partial = |algorithm| SearchOptions {
+                partial: true,
+                ..options(algorithm, movement)
+            };
+            let a_star = grid.find_path_with(start, end, &partial(Algorithm::AStar));
+            let jps = grid.find_path_with(start, end, &partial(Algorithm::JumpPoint));
+            assert_eq!(jps.outcome, SearchOutcome::Unreachable);
+            assert!(jps.partial);
+            assert_eq!(jps.path.last(), Some(&Point { x: 5, y: 9 }));
+            assert_eq!(jps.cost, a_star.cost);
+            assert_eq!(walk(&grid, movement, &jps.path), jps.cost);
+        }
+    }
+}
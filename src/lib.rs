@@ -1,11 +1,41 @@
-use std::slice::Iter;
-
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
 use std::fmt::Display;
 
+mod bidirectional;
+mod budget;
+mod clearance;
+mod field;
+mod graph;
+mod grid;
+mod heuristic;
+mod hex;
+mod hpa;
+mod jps;
+mod movement;
+mod options;
+mod search;
+mod sight;
+mod stepper;
+#[cfg(test)]
+mod testing;
+mod theta;
+
+pub use bidirectional::bidirectional_search;
+pub use field::{FlowField, NO_DIRECTION};
+pub use graph::{search, Goal, Graph, GraphPath, Reversed};
+pub use grid::{Grid, GridGraph, PointGoal, PointsGoal, IMPASSABLE};
+pub use heuristic::{Estimate, Heuristic, Weighted};
+pub use hex::{hex_a_star, Hex, HexGoal, HexGrid, HexOrientation, HexPathResult};
+pub use hpa::Hierarchy;
+pub use movement::{Movement, DIAGONAL_COST, ORTHOGONAL_COST};
+pub use options::{Algorithm, SearchOptions};
+pub use stepper::{AStarSearch, SNAPSHOT_STRIDE};
+pub use theta::{AnyAngle, AnyAnglePath, Waypoint};
+
 /// Point represents an x,y coordinate on a grid'
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(PartialEq, Clone, Debug, Copy)]
 pub struct Point {
     pub x: i32,
@@ -35,15 +65,15 @@ impl Point {
 ///
 /// pos: `Point` -> The position of the Node in the grid
 ///
-/// g: `i32` -> The cost of movement from the start Point to this Node's pos
+/// g: `i32` -> The cost of movement from the start Point to this Node's pos, where each step costs the step length times the cost of the cell entered
 ///
 /// h: `i32` -> The estimated cost of movement from this Node's pos to the end Point
 ///
 /// f: `i32` -> The combined cost of g and h, representing the total estimated cost to move from start to finish along this Node's path
 ///
-/// NOTE: This implementation uses the Manhattan Distance Heuristic to calculate `h`
+/// NOTE: `h` comes from the search's `Heuristic`, which defaults to Manhattan for 4-way movement and Octile for 8-way
 ///
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[derive(Debug, Clone)]
 pub struct Node {
     pub parent: Option<Point>,
@@ -59,127 +89,144 @@ impl PartialEq for Node {
     }
 }
 
-const MOVE_COST: i32 = 1;
-impl Node {
-    fn new(pos: Point, parent_node: Option<Node>, end_pos: &Point) -> Self {
-        let base_g = 0;
-        let base_h = (pos.x - end_pos.x).abs() + (pos.y - end_pos.y).abs();
-
-        let (parent, g, h, f) = if let Some(parent) = parent_node {
-            let g = base_g + parent.g + MOVE_COST;
-            let h = base_h;
-            let f = g + h;
-            (Some(parent.pos), g, h, f)
-        } else {
-            let g = base_g;
-            let h = base_h;
-            let f = g + h;
-            (None, g, h, f)
-        };
+/// SearchOutcome describes how a search ended
+///
+/// Found -> A path from the start Point to the end Point was found
+///
+/// Unreachable -> The open list was exhausted without reaching the end Point
+///
+/// StartBlocked -> The start Point is a wall
+///
+/// GoalBlocked -> The end Point is a wall
+///
+/// OutOfBounds -> The start or end Point lies outside the grid
+///
+/// BudgetExceeded -> The search was stopped by a limit before reaching the end Point
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SearchOutcome {
+    Found,
+    Unreachable,
+    StartBlocked,
+    GoalBlocked,
+    OutOfBounds,
+    BudgetExceeded,
+}
+
+/// PathResult is the outcome of a search, reconstructed from the `parent` chain of the end Node
+///
+/// outcome: `SearchOutcome` -> How the search ended. `path` is empty unless this is `Found`, it is `partial`,
+/// or a Bidirectional search ran out of budget after its halves met
+///
+/// path: `Vec<Point>` -> The ordered list of Points from the start Point to the end Point, inclusive
+///
+/// cost: `i32` -> The total movement cost of the path, equal to the `g` of the end Node.
+/// Costs are in tenths of a cell: `ORTHOGONAL_COST` per straight step and `DIAGONAL_COST` per diagonal one
+///
+/// expanded: `usize` -> The number of Nodes moved to the closed list during the search
+///
+/// partial: `bool` -> Whether the path stops short of the end Point, at the explored Node closest to it.
+/// Searches that run out of budget return partial paths, as do unreachable ones with `SearchOptions::partial`.
+/// A Bidirectional search that runs out after its two halves have met returns the whole path instead,
+/// with `BudgetExceeded` since it may not be the cheapest, and partial unset
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Debug, Clone)]
+pub struct PathResult {
+    pub outcome: SearchOutcome,
+    pub path: Vec<Point>,
+    pub cost: i32,
+    pub expanded: usize,
+    pub partial: bool,
+}
 
+impl PathResult {
+    /// Build a result for a search that did not reach the end Point
+    pub(crate) fn failed(outcome: SearchOutcome, expanded: usize) -> Self {
         Self {
-            parent,
-            pos,
-            g,
-            h,
-            f,
+            outcome,
+            path: Vec::new(),
+            cost: 0,
+            expanded,
+            partial: false,
         }
     }
 }
 
-enum Dirs {
-    N,
-    // NE,
-    E,
-    // SE,
-    S,
-    // SW,
-    W,
-    // NW,
-}
-
-impl Dirs {
-    fn get(dir: &Dirs) -> Point {
-        match dir {
-            Dirs::N => Point { x: 0, y: -1 },
-            // Dirs::NE => Point { x: 1, y: -1 },
-            Dirs::E => Point { x: 1, y: 0 },
-            // Dirs::SE => Point { x: 1, y: 1 },
-            Dirs::S => Point { x: 0, y: 1 },
-            // Dirs::SW => Point { x: -1, y: 1 },
-            Dirs::W => Point { x: -1, y: 0 },
-            // Dirs::NW => Point { x: -1, y: -1 },
+impl From<GraphPath<Point>> for PathResult {
+    fn from(graph_path: GraphPath<Point>) -> Self {
+        Self {
+            partial: graph_path.outcome != SearchOutcome::Found && !graph_path.path.is_empty(),
+            outcome: graph_path.outcome,
+            path: graph_path.path,
+            cost: graph_path.cost,
+            expanded: graph_path.expanded,
         }
     }
+}
+
+/// MultiGoalResult is the outcome of a search towards several end Points, with the same meaning as `PathResult`
+///
+/// goal: `Option<usize>` -> The position in the list of ends of the end Point the path leads to, if one was reached
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Debug, Clone)]
+pub struct MultiGoalResult {
+    pub outcome: SearchOutcome,
+    pub path: Vec<Point>,
+    pub cost: i32,
+    pub expanded: usize,
+    pub partial: bool,
+    pub goal: Option<usize>,
+}
 
-    fn iter() -> Iter<'static, Dirs> {
-        static D: [Dirs; 4] = [
-            Dirs::N,
-            // Dirs::NE,
-            Dirs::E,
-            // Dirs::SE,
-            Dirs::S,
-            // Dirs::SW,
-            Dirs::W,
-            // Dirs::NW,
-        ];
-        D.iter()
+impl MultiGoalResult {
+    /// Attach to result which of ends its path leads to
+    fn new(result: PathResult, ends: &[Point]) -> Self {
+        let goal = result
+            .path
+            .last()
+            .and_then(|reached| ends.iter().position(|end| end == reached));
+        Self {
+            outcome: result.outcome,
+            path: result.path,
+            cost: result.cost,
+            expanded: result.expanded,
+            partial: result.partial,
+            goal,
+        }
     }
 }
 
-#[wasm_bindgen]
-#[allow(clippy::same_item_push)] // Required because width and height are not constant when building the vector matrix
+/// Search a grid of the given size for the shortest path from start to end.
+/// Prefer a `Grid` when running many searches on the same map
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn a_star(
     width: usize,
     height: usize,
     start: Point,
     end: Point,
     walls: Vec<Point>,
-) -> Node {
-    let mut open: Vec<Node> = Vec::new();
-    let mut closed: Vec<Node> = Vec::new();
-
-    open.push(Node::new(start, None, &end));
-
-    loop {
-        if open.is_empty() {
-            panic!("No nodes in open list, end not found!")
-        }
-
-        // Sort so Node with lowest f value is last in list
-        open.sort_by(|a, b| b.f.cmp(&a.f));
-        let best_node = open.pop().expect("No node found on open list");
-
-        // Found end node, end search
-        if best_node.pos.eq(&end) {
-            break best_node;
-        }
+    options: Option<SearchOptions>,
+) -> PathResult {
+    Grid::with_walls(width, height, walls).find_path_with(start, end, &options.unwrap_or_default())
+}
 
-        // TODO: is there any way not to clone this instance?
-        // I am concerned that it will impact performance, especially in the for loop below
-        closed.push(best_node.clone());
-
-        for dir in Dirs::iter() {
-            let direction = Dirs::get(dir);
-            let successor = Node::new(
-                Point::add(&best_node.pos, &direction),
-                Some(best_node.clone()),
-                &end,
-            );
-
-            let next_pos = &successor.pos;
-            if next_pos.x >= width as i32
-                || next_pos.x < 0
-                || next_pos.y >= height as i32
-                || next_pos.y < 0
-                || walls.contains(next_pos)
-            {
-                continue;
-            }
-
-            if !open.contains(&successor) && !closed.contains(&successor) {
-                open.push(successor)
-            }
-        }
-    }
+/// Search a grid of the given size for the shortest path from start to the nearest of ends.
+/// Prefer a `Grid` when running many searches on the same map
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn a_star_nearest(
+    width: usize,
+    height: usize,
+    start: Point,
+    ends: Vec<Point>,
+    walls: Vec<Point>,
+    options: Option<SearchOptions>,
+) -> MultiGoalResult {
+    Grid::with_walls(width, height, walls).find_path_to_nearest(
+        start,
+        ends,
+        &options.unwrap_or_default(),
+    )
 }
@@ -0,0 +1,133 @@
+use std::slice::Iter;
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::grid::Grid;
+use crate::heuristic::Heuristic;
+use crate::Point;
+
+/// Cost of moving one cell horizontally or vertically
+pub const ORTHOGONAL_COST: i32 = 10;
+
+/// Cost of moving one cell diagonally, an integer approximation of `ORTHOGONAL_COST * √2`
+pub const DIAGONAL_COST: i32 = 14;
+
+///
+/// Movement selects which neighbors of a cell can be moved to
+///
+/// FourWay -> Only N, E, S and W
+///
+/// EightWay -> All eight neighbors, even when squeezing diagonally between two walls
+///
+/// EightWayNoCornerCutting -> Diagonals only when both orthogonal cells beside the move are open
+///
+/// EightWayOneCorner -> Diagonals when at least one orthogonal cell beside the move is open
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Movement {
+    #[default]
+    FourWay,
+    EightWay,
+    EightWayNoCornerCutting,
+    EightWayOneCorner,
+}
+
+impl Movement {
+    /// Directions that can be moved in, before checking for walls
+    pub(crate) fn dirs(&self) -> Iter<'static, Dirs> {
+        match self {
+            Movement::FourWay => Dirs::orthogonal(),
+            _ => Dirs::iter(),
+        }
+    }
+
+    /// Whether an agent of agent_size cells square moving from pos in direction dir stays on the grid
+    /// and is allowed past nearby walls
+    pub(crate) fn can_move(&self, grid: &Grid, pos: &Point, dir: &Dirs, agent_size: usize) -> bool {
+        self.allows(|x, y| grid.fits(x, y, agent_size), pos, dir)
+    }
+
+    /// Whether a move from pos in direction dir is allowed, given which cells are open
+    pub(crate) fn allows(&self, open: impl Fn(i32, i32) -> bool, pos: &Point, dir: &Dirs) -> bool {
+        let step = Dirs::get(dir);
+        if !open(pos.x + step.x, pos.y + step.y) {
+            return false;
+        }
+        if step.x == 0 || step.y == 0 {
+            return true;
+        }
+
+        let horizontal = open(pos.x + step.x, pos.y);
+        let vertical = open(pos.x, pos.y + step.y);
+        match self {
+            Movement::FourWay => false,
+            Movement::EightWay => true,
+            Movement::EightWayNoCornerCutting => horizontal && vertical,
+            Movement::EightWayOneCorner => horizontal || vertical,
+        }
+    }
+
+    /// The Heuristic used when none is chosen: Manhattan for 4-way movement, Octile otherwise
+    pub(crate) fn default_heuristic(&self) -> Heuristic {
+        match self {
+            Movement::FourWay => Heuristic::Manhattan,
+            _ => Heuristic::Octile,
+        }
+    }
+}
+
+/// Cost of a single step between two neighboring Points
+pub(crate) fn step_cost(from: &Point, to: &Point) -> i32 {
+    if from.x != to.x && from.y != to.y {
+        DIAGONAL_COST
+    } else {
+        ORTHOGONAL_COST
+    }
+}
+
+pub(crate) enum Dirs {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Dirs {
+    pub(crate) fn get(dir: &Dirs) -> Point {
+        match dir {
+            Dirs::N => Point { x: 0, y: -1 },
+            Dirs::NE => Point { x: 1, y: -1 },
+            Dirs::E => Point { x: 1, y: 0 },
+            Dirs::SE => Point { x: 1, y: 1 },
+            Dirs::S => Point { x: 0, y: 1 },
+            Dirs::SW => Point { x: -1, y: 1 },
+            Dirs::W => Point { x: -1, y: 0 },
+            Dirs::NW => Point { x: -1, y: -1 },
+        }
+    }
+
+    pub(crate) fn iter() -> Iter<'static, Dirs> {
+        static D: [Dirs; 8] = [
+            Dirs::N,
+            Dirs::NE,
+            Dirs::E,
+            Dirs::SE,
+            Dirs::S,
+            Dirs::SW,
+            Dirs::W,
+            Dirs::NW,
+        ];
+        D.iter()
+    }
+
+    pub(crate) fn orthogonal() -> Iter<'static, Dirs> {
+        static D: [Dirs; 4] = [Dirs::N, Dirs::E, Dirs::S, Dirs::W];
+        D.iter()
+    }
+}
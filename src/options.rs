@@ -0,0 +1,120 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::heuristic::Heuristic;
+use crate::movement::Movement;
+use crate::search::Priority;
+
+///
+/// Algorithm selects how the search expands the grid
+///
+/// AStar -> Expand the Node with the lowest g + h first. With a `weight` above 1 this is Weighted A*
+///
+/// JumpPoint -> Jump Point Search, which returns paths as short as AStar while expanding far fewer Nodes.
+/// Only used on grids where every open cell costs 1 and for searches without `partial`, others fall back to AStar
+///
+/// Dijkstra -> Expand the Node with the lowest g first, ignoring h. Always finds the shortest path
+///
+/// GreedyBestFirst -> Expand the Node with the lowest h first, ignoring g. Fast, but paths may be long
+///
+/// Bidirectional -> A* from start and end at once, stopping when the two searches meet on a shortest path.
+/// Step-by-step searches run it as AStar
+///
+/// Every Algorithm reports its ordering value as `f`, so `f` is g for Dijkstra and h for GreedyBestFirst
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Algorithm {
+    #[default]
+    AStar,
+    JumpPoint,
+    Dijkstra,
+    GreedyBestFirst,
+    Bidirectional,
+}
+
+impl Algorithm {
+    /// What orders the open list for this Algorithm
+    pub(crate) fn priority(&self) -> Priority {
+        match self {
+            Algorithm::AStar | Algorithm::JumpPoint | Algorithm::Bidirectional => Priority::GPlusH,
+            Algorithm::Dijkstra => Priority::G,
+            Algorithm::GreedyBestFirst => Priority::H,
+        }
+    }
+}
+
+///
+/// SearchOptions configures a single search
+///
+/// algorithm: `Algorithm` -> How the grid is expanded. Defaults to `AStar`
+///
+/// movement: `Movement` -> Which neighbors of a cell can be moved to. Defaults to `FourWay`
+///
+/// heuristic: `Option<Heuristic>` -> The distance estimate used for `h`. Defaults to the one matching `movement`
+///
+/// weight: `f32` -> Multiplier applied to `h`. Defaults to 1, values above 1 may return longer paths faster
+///
+/// max_expanded: `Option<usize>` -> The most Nodes the search may expand before giving up. Defaults to no limit
+///
+/// max_cost: `Option<i32>` -> The highest path cost the search may consider before giving up. Defaults to no limit
+///
+/// time_limit: `Option<f64>` -> Milliseconds the search may run for before giving up. Defaults to no limit.
+/// The clock is only read every few expansions, so a search can run slightly past it.
+/// An AStarSearch only counts the time spent inside `step`, `step_n` and `run`, not the time between calls
+///
+/// A search that gives up reports `BudgetExceeded`, with a path to the explored Node closest to the end Point
+///
+/// agent_size: `usize` -> The width and height in cells of the square agent the path is for. Defaults to 1.
+/// Points are the top left cell of the agent, and only cells where all of it fits on walkable cells are used
+///
+/// partial: `bool` -> Whether a search that cannot reach the end Point returns a path to the explored Node
+/// closest to it, with the lowest `h`, instead of no path. Defaults to false.
+/// JumpPoint searches with partial run as AStar, since jumps skip over the cells closest to the end
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy)]
+pub struct SearchOptions {
+    pub algorithm: Algorithm,
+    pub movement: Movement,
+    pub heuristic: Option<Heuristic>,
+    pub weight: f32,
+    pub max_expanded: Option<usize>,
+    pub max_cost: Option<i32>,
+    pub time_limit: Option<f64>,
+    pub partial: bool,
+    pub agent_size: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::default(),
+            movement: Movement::default(),
+            heuristic: None,
+            weight: 1.0,
+            max_expanded: None,
+            max_cost: None,
+            time_limit: None,
+            partial: false,
+            agent_size: 1,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SearchOptions {
+    /// Create options with every setting at its default
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SearchOptions {
+    /// The Heuristic chosen for this search, falling back to the one matching `movement`
+    pub(crate) fn heuristic(&self) -> Heuristic {
+        self.heuristic
+            .unwrap_or_else(|| self.movement.default_heuristic())
+    }
+}
@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::budget::Budget;
+use crate::graph::{Goal, Graph, GraphPath};
+use crate::grid::Grid;
+use crate::{Node, SearchOutcome};
+
+/// OpenNode is an entry on the open list, ordered so the BinaryHeap pops the lowest `f` first,
+/// breaking ties in favor of the lowest `h` (the node closest to the goal)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OpenNode {
+    pub(crate) index: usize,
+    pub(crate) g: i32,
+    pub(crate) h: i32,
+    pub(crate) f: i32,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.f == other.f && self.h == other.h
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.cmp(&self.f).then_with(|| other.h.cmp(&self.h))
+    }
+}
+
+///
+/// Priority selects what orders the open list, and so what is stored as `f`
+///
+/// GPlusH -> g + h, A*
+///
+/// G -> g alone, Dijkstra's algorithm
+///
+/// H -> h alone, Greedy Best-First search, which never revisits a node once it has been reached
+///
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub(crate) enum Priority {
+    GPlusH,
+    G,
+    H,
+}
+
+impl Priority {
+    fn f(&self, g: i32, h: i32) -> i32 {
+        match self {
+            Priority::GPlusH => g.saturating_add(h),
+            Priority::G => g,
+            Priority::H => h,
+        }
+    }
+}
+
+///
+/// SearchState is everything a search carries between expansions, so it can be run to completion or one step at a time
+///
+/// Best known g for every index, the parent it was reached from, and whether it has been expanded.
+/// A cheaper route to an open node pushes a fresh entry, and the stale one is skipped when popped.
+/// A cheaper route to a closed node reopens it, which keeps the path optimal for inconsistent heuristics
+///
+/// The expanded node with the lowest `h` is kept as `closest`, so a search stopped by its Budget, or one
+/// asked for a `partial` path, can still return a path that heads towards the goal
+///
+pub(crate) struct SearchState {
+    pub(crate) priority: Priority,
+    pub(crate) best_g: Vec<i32>,
+    pub(crate) parents: Vec<Option<usize>>,
+    pub(crate) closed: Vec<bool>,
+    pub(crate) open: BinaryHeap<OpenNode>,
+    pub(crate) expanded: usize,
+    pub(crate) current: Option<OpenNode>,
+    pub(crate) outcome: Option<SearchOutcome>,
+    pub(crate) found: Option<usize>,
+    pub(crate) closest: Option<OpenNode>,
+    pub(crate) budget: Budget,
+    pub(crate) partial: bool,
+}
+
+impl SearchState {
+    /// Start a search from start towards goal
+    pub(crate) fn new<G: Graph, Q: Goal<G::Node>>(
+        graph: &G,
+        start: G::Node,
+        goal: &Q,
+        priority: Priority,
+    ) -> Self {
+        Self::from_starts(graph, [start], goal, priority)
+    }
+
+    /// Start a search from every one of starts at once, as if each were one step from a shared start
+    pub(crate) fn from_starts<G: Graph, Q: Goal<G::Node>>(
+        graph: &G,
+        starts: impl IntoIterator<Item = G::Node>,
+        goal: &Q,
+        priority: Priority,
+    ) -> Self {
+        let mut state = Self {
+            priority,
+            best_g: vec![i32::MAX; graph.node_count()],
+            parents: vec![None; graph.node_count()],
+            closed: vec![false; graph.node_count()],
+            open: BinaryHeap::new(),
+            expanded: 0,
+            current: None,
+            outcome: None,
+            found: None,
+            closest: None,
+            budget: Budget::default(),
+            partial: false,
+        };
+
+        for start in starts {
+            let index = graph.index(start);
+            let h = goal.estimate(start);
+            state.best_g[index] = 0;
+            state.open.push(OpenNode {
+                index,
+                g: 0,
+                h,
+                f: priority.f(0, h),
+            });
+        }
+        state
+    }
+
+    /// A search that finished before it started, because its start or end cannot be used
+    pub(crate) fn failed(outcome: SearchOutcome) -> Self {
+        Self {
+            priority: Priority::GPlusH,
+            best_g: Vec::new(),
+            parents: Vec::new(),
+            closed: Vec::new(),
+            open: BinaryHeap::new(),
+            expanded: 0,
+            current: None,
+            outcome: Some(outcome),
+            found: None,
+            closest: None,
+            budget: Budget::default(),
+            partial: false,
+        }
+    }
+
+    /// Expand the best node on the open list, returning whether the search is finished
+    pub(crate) fn step<G: Graph, Q: Goal<G::Node>>(&mut self, graph: &G, goal: &Q) -> bool {
+        self.step_with(graph, goal, |node, _| graph.successors(node))
+    }
+
+    /// Expand the best node on the open list using successors, which is given the node and its parent,
+    /// returning whether the search is finished
+    pub(crate) fn step_with<G, Q, F, I>(&mut self, graph: &G, goal: &Q, mut successors: F) -> bool
+    where
+        G: Graph,
+        Q: Goal<G::Node>,
+        F: FnMut(G::Node, Option<G::Node>) -> I,
+        I: IntoIterator<Item = (G::Node, i32)>,
+    {
+        if self.outcome.is_some() {
+            return true;
+        }
+
+        let best = loop {
+            let Some(entry) = self.open.pop() else {
+                self.outcome = Some(SearchOutcome::Unreachable);
+                return true;
+            };
+
+            if !self.closed[entry.index] && entry.g <= self.best_g[entry.index] {
+                break entry;
+            }
+        };
+        if self.budget.too_costly(best.g) {
+            self.outcome = Some(SearchOutcome::BudgetExceeded);
+            return true;
+        }
+
+        // Found end node, end search
+        let node = graph.node(best.index);
+        if goal.is_goal(node) {
+            self.current = Some(best);
+            self.outcome = Some(SearchOutcome::Found);
+            self.found = Some(best.index);
+            return true;
+        }
+
+        // Reaching the goal takes no expansion, so the budget is only checked before expanding anything else
+        if self.budget.exhausted(self.expanded) {
+            self.open.push(best);
+            self.outcome = Some(SearchOutcome::BudgetExceeded);
+            return true;
+        }
+        self.current = Some(best);
+
+        self.closed[best.index] = true;
+        self.expanded += 1;
+        if self
+            .closest
+            .is_none_or(|closest| (best.h, best.g) < (closest.h, closest.g))
+        {
+            self.closest = Some(best);
+        }
+
+        let parent = self.parents[best.index].map(|index| graph.node(index));
+        for (next, cost) in successors(node, parent) {
+            let index = graph.index(next);
+            let g = best.g.saturating_add(cost);
+            let improves = match self.priority {
+                Priority::H => self.best_g[index] == i32::MAX,
+                _ => g < self.best_g[index],
+            };
+            if improves {
+                let h = goal.estimate(next);
+                self.best_g[index] = g;
+                self.closed[index] = false;
+                self.parents[index] = Some(best.index);
+                self.open.push(OpenNode {
+                    index,
+                    g,
+                    h,
+                    f: self.priority.f(g, h),
+                });
+            }
+        }
+        false
+    }
+
+    /// The path found by a finished search, walking the parent table back from the goal.
+    /// A search stopped by its Budget, or an unreachable one asked for a `partial` path, walks back from the closest node instead
+    pub(crate) fn path<G: Graph>(&self, graph: &G) -> GraphPath<G::Node> {
+        let end = match self.outcome {
+            Some(SearchOutcome::BudgetExceeded) => self.closest.map(|closest| closest.index),
+            Some(SearchOutcome::Unreachable) if self.partial => {
+                self.closest.map(|closest| closest.index)
+            }
+            _ => self.found,
+        };
+        let mut path = Vec::new();
+        let mut parent = end;
+        while let Some(index) = parent {
+            path.push(graph.node(index));
+            parent = self.parents[index];
+        }
+        path.reverse();
+
+        GraphPath {
+            outcome: self.outcome.unwrap_or(SearchOutcome::Unreachable),
+            path,
+            cost: end.map_or(0, |index| self.best_g[index]),
+            expanded: self.expanded,
+        }
+    }
+
+    /// The grid Node for an entry on the open or closed list
+    pub(crate) fn grid_node(&self, grid: &Grid, entry: &OpenNode) -> Node {
+        Node {
+            parent: self.parents[entry.index].map(|index| grid.point(index)),
+            pos: grid.point(entry.index),
+            g: entry.g,
+            h: entry.h,
+            f: entry.f,
+        }
+    }
+
+    /// Entries on the closed list, with `h` estimated again since only `g` is kept for them
+    pub(crate) fn closed_nodes<'a, G: Graph, Q: Goal<G::Node>>(
+        &'a self,
+        graph: &'a G,
+        goal: &'a Q,
+    ) -> impl Iterator<Item = OpenNode> + 'a {
+        self.closed
+            .iter()
+            .enumerate()
+            .filter(|(_, closed)| **closed)
+            .map(move |(index, _)| {
+                let g = self.best_g[index];
+                let h = goal.estimate(graph.node(index));
+                OpenNode {
+                    index,
+                    g,
+                    h,
+                    f: self.priority.f(g, h),
+                }
+            })
+    }
+
+    /// Entries on the open list, skipping those superseded by a cheaper route
+    pub(crate) fn open_nodes(&self) -> impl Iterator<Item = &OpenNode> {
+        self.open
+            .iter()
+            .filter(|entry| !self.closed[entry.index] && entry.g == self.best_g[entry.index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::PointGoal;
+    use crate::heuristic::Heuristic;
+    use crate::testing::{dijkstra, random_grid, walk, Rng, MOVEMENTS};
+    use crate::{Algorithm, Point, SearchOptions};
+
+    #[test]
+    fn matches_dijkstra_on_random_grids() {
+        let mut rng = Rng::new(4);
+        for round in 0..3000 {
+            let (width, height) = (rng.range(1, 16) as usize, rng.range(1, 16) as usize);
+            let grid = random_grid(&mut rng, width, height, 4, round % 2 == 0);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            if grid.check_endpoints(&start, &end).is_some() {
+                continue;
+            }
+
+            for movement in MOVEMENTS {
+                let graph = grid.graph(movement);
+                let heuristic = movement.default_heuristic();
+                let goal = PointGoal {
+                    end,
+                    estimate: &heuristic,
+                };
+                let mut state = SearchState::new(&graph, start, &goal, Priority::GPlusH);
+                while !state.step(&graph, &goal) {}
+                let result = state.path(&graph);
+
+                match dijkstra(&grid, movement, start, end) {
+                    Some(cost) => {
+                        assert_eq!(result.outcome, SearchOutcome::Found);
+                        assert_eq!(result.cost, cost, "{movement:?} from {start} to {end}");
+                        assert_eq!(result.path.first(), Some(&start));
+                        assert_eq!(result.path.last(), Some(&end));
+                        assert_eq!(walk(&grid, movement, &result.path), result.cost);
+                    }
+                    None => {
+                        assert_eq!(result.outcome, SearchOutcome::Unreachable);
+                        assert!(result.path.is_empty());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dijkstra_priority_finds_cheapest_path() {
+        let mut rng = Rng::new(44);
+        for _ in 0..500 {
+            let grid = random_grid(&mut rng, 12, 12, 4, true);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            if grid.check_endpoints(&start, &end).is_some() {
+                continue;
+            }
+
+            for movement in MOVEMENTS {
+                let graph = grid.graph(movement);
+                let goal = PointGoal {
+                    end,
+                    estimate: &Heuristic::Zero,
+                };
+                let mut state = SearchState::new(&graph, start, &goal, Priority::G);
+                while !state.step(&graph, &goal) {}
+                let result = state.path(&graph);
+                assert_eq!(
+                    (result.outcome == SearchOutcome::Found).then_some(result.cost),
+                    dijkstra(&grid, movement, start, end)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn goal_is_found_before_the_budget_stops_the_search() {
+        let grid = Grid::new(4, 4);
+        let start = Point { x: 1, y: 2 };
+        for algorithm in [
+            Algorithm::AStar,
+            Algorithm::JumpPoint,
+            Algorithm::Dijkstra,
+            Algorithm::GreedyBestFirst,
+            Algorithm::Bidirectional,
+        ] {
+            let options = SearchOptions {
+                algorithm,
+                max_expanded: Some(0),
+                ..SearchOptions::default()
+            };
+            let result = grid.find_path_with(start, start, &options);
+            assert_eq!(result.outcome, SearchOutcome::Found, "{algorithm:?}");
+            assert_eq!(result.path, vec![start]);
+
+            let result = grid.find_path_with(start, Point { x: 3, y: 3 }, &options);
+            assert_eq!(
+                result.outcome,
+                SearchOutcome::BudgetExceeded,
+                "{algorithm:?}"
+            );
+            assert!(result.path.is_empty());
+        }
+    }
+}
@@ -0,0 +1,309 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::grid::{Grid, GridGraph};
+use crate::movement::Movement;
+use crate::theta::Waypoint;
+use crate::Point;
+
+/// How close, in cells along a ray, the next column and row edges must be for the ray to count as crossing a corner
+const CORNER_TOLERANCE: f64 = 1e-9;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Grid {
+    /// Shorten path by pulling it tight: a waypoint is kept only when the line between the waypoints
+    /// either side of it is blocked. Lines follow the same corner rules as movement.
+    /// Only walls are checked, so on a grid with a cost layer the smoothed path may cross more expensive cells
+    pub fn smooth_path(&self, path: Vec<Point>, movement: Movement) -> Vec<Point> {
+        let (Some(&first), Some(&last)) = (path.first(), path.last()) else {
+            return path;
+        };
+
+        let mut smoothed = vec![first];
+        for pair in path.windows(2).skip(1) {
+            let (previous, next) = (pair[0], pair[1]);
+            let anchor = smoothed[smoothed.len() - 1];
+            if !self.line_of_sight(anchor, next, &self.graph(movement)) {
+                smoothed.push(previous);
+            }
+        }
+        if path.len() > 1 {
+            smoothed.push(last);
+        }
+        smoothed
+    }
+
+    /// Whether the straight line between the centers of a and b passes only through walkable cells.
+    /// A line passing exactly between two diagonal cells is blocked if either of them is a wall
+    pub fn has_line_of_sight(&self, a: Point, b: Point) -> bool {
+        self.line_of_sight(a, b, &self.graph(Movement::EightWayNoCornerCutting))
+    }
+
+    /// Follow a ray from origin, a position in cells, along direction, a Waypoint holding its x and y components,
+    /// for up to max_distance cells, returning the first cell it enters that is not walkable.
+    /// Cells outside the grid block the ray, and a ray passing exactly between two diagonal cells
+    /// is blocked if either of them is a wall
+    pub fn raycast(
+        &self,
+        origin: Waypoint,
+        direction: Waypoint,
+        max_distance: f64,
+    ) -> Option<Point> {
+        let mut cell = Point {
+            x: origin.x.floor() as i32,
+            y: origin.y.floor() as i32,
+        };
+        if !self.is_walkable(cell.x, cell.y) {
+            return Some(cell);
+        }
+        let length = direction.x.hypot(direction.y);
+        if length == 0.0 || !length.is_finite() {
+            return None;
+        }
+
+        // Distance along the ray to the next column and row edge, and between consecutive ones
+        let (dx, dy) = (direction.x / length, direction.y / length);
+        let (step_x, step_y) = (dx.signum() as i32, dy.signum() as i32);
+        let first_edge = |position: f64, cell: i32, delta: f64| {
+            if delta > 0.0 {
+                (f64::from(cell) + 1.0 - position) / delta
+            } else if delta < 0.0 {
+                (position - f64::from(cell)) / -delta
+            } else {
+                f64::INFINITY
+            }
+        };
+        let mut next_x = first_edge(origin.x, cell.x, dx);
+        let mut next_y = first_edge(origin.y, cell.y, dy);
+        let (delta_x, delta_y) = (1.0 / dx.abs(), 1.0 / dy.abs());
+
+        loop {
+            if next_x.min(next_y) > max_distance {
+                return None;
+            }
+            // Within rounding of a corner, so the ray passes exactly between two diagonal cells
+            if (next_x - next_y).abs() < CORNER_TOLERANCE {
+                let horizontal = Point {
+                    x: cell.x + step_x,
+                    y: cell.y,
+                };
+                let vertical = Point {
+                    x: cell.x,
+                    y: cell.y + step_y,
+                };
+                for side in [horizontal, vertical] {
+                    if !self.is_walkable(side.x, side.y) {
+                        return Some(side);
+                    }
+                }
+                cell = Point {
+                    x: cell.x + step_x,
+                    y: cell.y + step_y,
+                };
+                next_x += delta_x;
+                next_y += delta_y;
+            } else if next_x < next_y {
+                cell.x += step_x;
+                next_x += delta_x;
+            } else {
+                cell.y += step_y;
+                next_y += delta_y;
+            }
+
+            if !self.is_walkable(cell.x, cell.y) {
+                return Some(cell);
+            }
+        }
+    }
+}
+
+impl Grid {
+    /// Whether every cell the straight line between the centers of from and to passes through can be entered in graph
+    pub(crate) fn line_of_sight(&self, from: Point, to: Point, graph: &GridGraph) -> bool {
+        self.first_blocked(from, to, graph).is_none()
+    }
+
+    /// The first cell that blocks the straight line from the center of from to the center of to,
+    /// visiting every cell the line touches. Where the line crosses exactly through a corner,
+    /// the two cells beside it are checked with the corner rules of the movement in graph
+    pub(crate) fn first_blocked(&self, from: Point, to: Point, graph: &GridGraph) -> Option<Point> {
+        let open = |pos: Point| self.fits(pos.x, pos.y, graph.agent_size);
+        if !open(from) {
+            return Some(from);
+        }
+
+        let (nx, ny) = ((to.x - from.x).abs(), (to.y - from.y).abs());
+        let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+        let (mut ix, mut iy) = (0, 0);
+        let mut pos = from;
+        while ix < nx || iy < ny {
+            // Compare where the line crosses the next column edge with where it crosses the next row edge
+            let decision = (1 + 2 * ix) * ny - (1 + 2 * iy) * nx;
+            if decision == 0 {
+                let horizontal = Point {
+                    x: pos.x + sx,
+                    y: pos.y,
+                };
+                let vertical = Point {
+                    x: pos.x,
+                    y: pos.y + sy,
+                };
+                let horizontal_open = open(horizontal);
+                let vertical_open = open(vertical);
+                let corner_open = match graph.movement {
+                    Movement::EightWay => true,
+                    Movement::EightWayOneCorner => horizontal_open || vertical_open,
+                    Movement::FourWay | Movement::EightWayNoCornerCutting => {
+                        horizontal_open && vertical_open
+                    }
+                };
+                if !corner_open {
+                    return Some(if horizontal_open {
+                        vertical
+                    } else {
+                        horizontal
+                    });
+                }
+                pos = Point {
+                    x: pos.x + sx,
+                    y: pos.y + sy,
+                };
+                ix += 1;
+                iy += 1;
+            } else if decision < 0 {
+                pos.x += sx;
+                ix += 1;
+            } else {
+                pos.y += sy;
+                iy += 1;
+            }
+
+            if !open(pos) {
+                return Some(pos);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_grid, Rng, MOVEMENTS};
+    use crate::SearchOptions;
+
+    fn center(pos: Point) -> Waypoint {
+        Waypoint {
+            x: f64::from(pos.x) + 0.5,
+            y: f64::from(pos.y) + 0.5,
+        }
+    }
+
+    #[test]
+    fn raycast_agrees_with_line_of_sight() {
+        let mut rng = Rng::new(23);
+        for _ in 0..500 {
+            let grid = random_grid(&mut rng, 12, 12, 5, false);
+            for _ in 0..20 {
+                let (a, b) = (rng.point(&grid), rng.point(&grid));
+                let direction = Waypoint {
+                    x: f64::from(b.x - a.x),
+                    y: f64::from(b.y - a.y),
+                };
+                let distance = direction.x.hypot(direction.y);
+                let hit = grid.raycast(center(a), direction, distance);
+                assert_eq!(hit.is_none(), grid.has_line_of_sight(a, b), "{a} to {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_between_two_walls_is_blocked() {
+        let mut grid = Grid::new(2, 2);
+        grid.set_wall(1, 0);
+        grid.set_wall(0, 1);
+        let (a, b) = (Point { x: 0, y: 0 }, Point { x: 1, y: 1 });
+        assert!(!grid.has_line_of_sight(a, b));
+        let hit = grid.raycast(center(a), Waypoint { x: 1.0, y: 1.0 }, 10.0);
+        assert_eq!(hit, Some(Point { x: 1, y: 0 }));
+    }
+
+    #[test]
+    fn axis_aligned_rays() {
+        let mut grid = Grid::new(6, 1);
+        grid.set_wall(3, 0);
+        let right = Waypoint { x: 1.0, y: 0.0 };
+        let left = Waypoint { x: -1.0, y: 0.0 };
+        let origin = Waypoint { x: 0.5, y: 0.5 };
+        assert_eq!(
+            grid.raycast(origin, right, 10.0),
+            Some(Point { x: 3, y: 0 })
+        );
+        assert_eq!(grid.raycast(origin, right, 2.0), None);
+        assert_eq!(
+            grid.raycast(origin, left, 10.0),
+            Some(Point { x: -1, y: 0 })
+        );
+        let beyond = Waypoint { x: 4.5, y: 0.5 };
+        assert_eq!(grid.raycast(beyond, left, 10.0), Some(Point { x: 3, y: 0 }));
+        assert_eq!(
+            grid.raycast(beyond, right, 10.0),
+            Some(Point { x: 6, y: 0 })
+        );
+        let up = Waypoint { x: 0.0, y: -1.0 };
+        assert_eq!(grid.raycast(origin, up, 10.0), Some(Point { x: 0, y: -1 }));
+    }
+
+    #[test]
+    fn zero_direction_hits_nothing() {
+        let grid = Grid::new(3, 3);
+        let origin = Waypoint { x: 1.5, y: 1.5 };
+        assert_eq!(
+            grid.raycast(origin, Waypoint { x: 0.0, y: 0.0 }, 10.0),
+            None
+        );
+    }
+
+    /// Total straight line length of path, in cells
+    fn length(path: &[Point]) -> f64 {
+        path.windows(2)
+            .map(|pair| f64::from(pair[1].x - pair[0].x).hypot(f64::from(pair[1].y - pair[0].y)))
+            .sum()
+    }
+
+    #[test]
+    fn smoothed_paths_keep_their_ends_and_sight() {
+        let mut rng = Rng::new(21);
+        for _ in 0..200 {
+            let grid = random_grid(&mut rng, 16, 16, 5, false);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            for movement in MOVEMENTS {
+                let options = SearchOptions {
+                    movement,
+                    ..SearchOptions::default()
+                };
+                let path = grid.find_path_with(start, end, &options).path;
+                let smoothed = grid.smooth_path(path.clone(), movement);
+                assert_eq!(smoothed.first(), path.first());
+                assert_eq!(smoothed.last(), path.last());
+                assert!(smoothed.len() <= path.len());
+                assert!(length(&smoothed) <= length(&path) + 1e-9);
+                let graph = grid.graph(movement);
+                for pair in smoothed.windows(2) {
+                    assert!(grid.line_of_sight(pair[0], pair[1], &graph), "{movement:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn short_paths_are_unchanged() {
+        let grid = Grid::new(3, 3);
+        let (a, b) = (Point { x: 0, y: 0 }, Point { x: 1, y: 1 });
+        for movement in MOVEMENTS {
+            assert_eq!(grid.smooth_path(vec![], movement), vec![]);
+            assert_eq!(grid.smooth_path(vec![a], movement), vec![a]);
+            assert_eq!(grid.smooth_path(vec![a, b], movement), vec![a, b]);
+        }
+    }
+}
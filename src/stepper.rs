@@ -0,0 +1,209 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::budget::Budget;
+use crate::grid::{Grid, PointGoal};
+use crate::heuristic::Weighted;
+use crate::search::SearchState;
+use crate::{Node, PathResult, Point, SearchOptions};
+
+///
+/// An AStarSearch runs a search one expansion at a time, so each frame of the search can be drawn
+///
+/// The Grid is copied when the search is created, so later edits to it do not affect a running search
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct AStarSearch {
+    grid: Grid,
+    end: Point,
+    options: SearchOptions,
+    state: SearchState,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl AStarSearch {
+    /// Start a search from start to end, using the default SearchOptions when none are given
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(grid: &Grid, start: Point, end: Point, options: Option<SearchOptions>) -> Self {
+        let options = options.unwrap_or_default();
+        let grid = grid.clone();
+        let state = match grid.check_agent_endpoints(&start, &end, options.agent_size) {
+            Some(outcome) => SearchState::failed(outcome),
+            None => {
+                let heuristic = options.heuristic();
+                let estimate = Weighted {
+                    estimate: &heuristic,
+                    weight: options.weight,
+                };
+                let goal = PointGoal {
+                    end,
+                    estimate: &estimate,
+                };
+                let mut state = SearchState::new(
+                    &grid.graph_for(&options),
+                    start,
+                    &goal,
+                    options.algorithm.priority(),
+                );
+                state.budget = Budget::new(&options);
+                state.budget.pause();
+                state.partial = options.partial;
+                state
+            }
+        };
+        Self {
+            grid,
+            end,
+            options,
+            state,
+        }
+    }
+
+    /// Expand the next Node, returning whether the search is finished
+    pub fn step(&mut self) -> bool {
+        self.state.budget.resume();
+        let done = self.expand();
+        self.state.budget.pause();
+        done
+    }
+
+    /// Run the search to completion and return its result
+    pub fn run(&mut self) -> PathResult {
+        self.state.budget.resume();
+        while !self.expand() {}
+        self.state.budget.pause();
+        self.grid.path(&self.state, &self.options)
+    }
+
+    /// Expand up to n Nodes, returning whether the search is finished
+    pub fn step_n(&mut self, n: usize) -> bool {
+        self.state.budget.resume();
+        let done = (0..n).any(|_| self.expand());
+        self.state.budget.pause();
+        done || self.is_done()
+    }
+
+    /// Whether the search has finished, successfully or not
+    pub fn is_done(&self) -> bool {
+        self.state.outcome.is_some()
+    }
+
+    /// The result of the search, once it is finished
+    pub fn result(&self) -> Option<PathResult> {
+        self.is_done()
+            .then(|| self.grid.path(&self.state, &self.options))
+    }
+
+    /// The Node expanded by the last step, if any
+    pub fn current(&self) -> Option<Node> {
+        self.state
+            .current
+            .map(|entry| self.state.grid_node(&self.grid, &entry))
+    }
+
+    /// Positions of the Nodes waiting on the open list
+    pub fn open_set(&self) -> Vec<Point> {
+        self.state
+            .open_nodes()
+            .map(|entry| self.grid.point(entry.index))
+            .collect()
+    }
+
+    /// Positions of the Nodes already expanded
+    pub fn closed_set(&self) -> Vec<Point> {
+        self.state
+            .closed
+            .iter()
+            .enumerate()
+            .filter(|(_, closed)| **closed)
+            .map(|(index, _)| self.grid.point(index))
+            .collect()
+    }
+
+    /// The open list as a flat Int32Array with `SNAPSHOT_STRIDE` values per Node:
+    /// x, y, g, h, f, parent_x, parent_y. A Node without a parent has a parent of -1, -1
+    pub fn open_snapshot(&self) -> Vec<i32> {
+        snapshot(
+            self.state
+                .open_nodes()
+                .map(|entry| self.state.grid_node(&self.grid, entry)),
+        )
+    }
+
+    /// The closed list as a flat Int32Array, laid out the same way as `open_snapshot`
+    pub fn closed_snapshot(&self) -> Vec<i32> {
+        let heuristic = self.options.heuristic();
+        let estimate = Weighted {
+            estimate: &heuristic,
+            weight: self.options.weight,
+        };
+        let goal = PointGoal {
+            end: self.end,
+            estimate: &estimate,
+        };
+        let graph = self.grid.graph_for(&self.options);
+        snapshot(
+            self.state
+                .closed_nodes(&graph, &goal)
+                .map(|entry| self.state.grid_node(&self.grid, &entry)),
+        )
+    }
+}
+
+impl AStarSearch {
+    /// Expand the next Node without starting or stopping the time limit
+    fn expand(&mut self) -> bool {
+        let heuristic = self.options.heuristic();
+        let estimate = Weighted {
+            estimate: &heuristic,
+            weight: self.options.weight,
+        };
+        let goal = PointGoal {
+            end: self.end,
+            estimate: &estimate,
+        };
+        self.grid.step(&mut self.state, &self.options, &goal)
+    }
+}
+
+/// Number of values per Node in an open or closed snapshot
+pub const SNAPSHOT_STRIDE: usize = 7;
+
+fn snapshot(nodes: impl Iterator<Item = Node>) -> Vec<i32> {
+    let mut values = Vec::new();
+    for node in nodes {
+        let parent = node.parent.unwrap_or(Point { x: -1, y: -1 });
+        values.extend_from_slice(&[
+            node.pos.x, node.pos.y, node.g, node.h, node.f, parent.x, parent.y,
+        ]);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearchOutcome;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn time_limit_ignores_time_between_steps() {
+        let grid = Grid::new(8, 8);
+        let options = SearchOptions {
+            time_limit: Some(20.0),
+            ..SearchOptions::default()
+        };
+        let mut search = AStarSearch::new(
+            &grid,
+            Point { x: 0, y: 0 },
+            Point { x: 7, y: 7 },
+            Some(options),
+        );
+        sleep(Duration::from_millis(40));
+        while !search.step() {
+            sleep(Duration::from_millis(1));
+        }
+        assert_eq!(search.result().unwrap().outcome, SearchOutcome::Found);
+    }
+}
@@ -0,0 +1,109 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::graph::Graph;
+use crate::movement::{self, Movement};
+use crate::{Grid, Point};
+
+pub(crate) const MOVEMENTS: [Movement; 4] = [
+    Movement::FourWay,
+    Movement::EightWay,
+    Movement::EightWayNoCornerCutting,
+    Movement::EightWayOneCorner,
+];
+
+/// A small xorshift generator, so tests are repeatable without pulling in a crate
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in low..high
+    pub(crate) fn range(&mut self, low: i32, high: i32) -> i32 {
+        low + (self.next() % (high - low) as u64) as i32
+    }
+
+    /// True one time in n
+    pub(crate) fn one_in(&mut self, n: u64) -> bool {
+        self.next().is_multiple_of(n)
+    }
+
+    pub(crate) fn point(&mut self, grid: &Grid) -> Point {
+        Point {
+            x: self.range(0, grid.width() as i32),
+            y: self.range(0, grid.height() as i32),
+        }
+    }
+}
+
+/// A grid of the given size with about one cell in walls_one_in a wall,
+/// and when costs is set a random cost from 1 to 5 on every open cell
+pub(crate) fn random_grid(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    walls_one_in: u64,
+    costs: bool,
+) -> Grid {
+    let mut grid = Grid::new(width, height);
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            if rng.one_in(walls_one_in) {
+                grid.set_wall(x, y);
+            } else if costs {
+                grid.set_cost(x, y, rng.range(1, 6) as u32);
+            }
+        }
+    }
+    grid
+}
+
+/// Cost of the cheapest path from start to end, by a plain Dijkstra over every cell
+pub(crate) fn dijkstra(grid: &Grid, movement: Movement, start: Point, end: Point) -> Option<i32> {
+    let graph = grid.graph(movement);
+    let mut best = vec![i32::MAX; grid.len()];
+    let mut open = BinaryHeap::new();
+    best[grid.index(&start)] = 0;
+    open.push(Reverse((0, grid.index(&start))));
+    while let Some(Reverse((g, index))) = open.pop() {
+        if g > best[index] {
+            continue;
+        }
+        let pos = grid.point(index);
+        if pos == end {
+            return Some(g);
+        }
+        for (next, cost) in graph.successors(pos) {
+            let next_index = grid.index(&next);
+            if g + cost < best[next_index] {
+                best[next_index] = g + cost;
+                open.push(Reverse((g + cost, next_index)));
+            }
+        }
+    }
+    None
+}
+
+/// The cost of walking path one legal step at a time, panicking at any step movement does not allow
+pub(crate) fn walk(grid: &Grid, movement: Movement, path: &[Point]) -> i32 {
+    let graph = grid.graph(movement);
+    path.windows(2)
+        .map(|pair| {
+            let (from, to) = (pair[0], pair[1]);
+            assert!(
+                graph.successors(from).any(|(next, _)| next == to),
+                "{from} to {to} is not a legal step"
+            );
+            movement::step_cost(&from, &to) * grid.cost(to.x, to.y) as i32
+        })
+        .sum()
+}
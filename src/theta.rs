@@ -0,0 +1,324 @@
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::budget::Budget;
+use crate::graph::Graph;
+use crate::grid::{Grid, GridGraph};
+use crate::movement::ORTHOGONAL_COST;
+use crate::{Point, SearchOptions, SearchOutcome};
+
+///
+/// AnyAngle selects how an any-angle search decides which cell a path turns at
+///
+/// ThetaStar -> Checks line of sight from the parent of a cell to each new neighbor, so a path only
+/// turns at cells where it has to
+///
+/// LazyThetaStar -> Assumes line of sight when a neighbor is found and only checks it when the neighbor is expanded,
+/// which runs far fewer checks for paths that are almost as short
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum AnyAngle {
+    #[default]
+    ThetaStar,
+    LazyThetaStar,
+}
+
+/// Waypoint is a position on a grid in cells, where the center of the cell x,y is x + 0.5, y + 0.5
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(PartialEq, Clone, Debug, Copy)]
+pub struct Waypoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl From<Point> for Waypoint {
+    fn from(point: Point) -> Self {
+        Self {
+            x: f64::from(point.x) + 0.5,
+            y: f64::from(point.y) + 0.5,
+        }
+    }
+}
+
+/// AnyAnglePath is the outcome of an any-angle search, with the same meaning as `PathResult`
+///
+/// path: `Vec<Waypoint>` -> The centers of the start cell, every cell the path turns at, and the end cell
+///
+/// cost: `f64` -> The length of the path, in the same tenths of a cell as `PathResult::cost`
+///
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Debug, Clone)]
+pub struct AnyAnglePath {
+    pub outcome: SearchOutcome,
+    pub path: Vec<Waypoint>,
+    pub cost: f64,
+    pub expanded: usize,
+    pub partial: bool,
+}
+
+/// An entry on the any-angle open list, ordered like `OpenNode` but with real costs
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    index: usize,
+    g: f64,
+    h: f64,
+    f: f64,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .f
+            .total_cmp(&self.f)
+            .then_with(|| other.h.total_cmp(&self.h))
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Grid {
+    /// Search for the shortest path from start to end that may travel at any angle, turning only at cell centers.
+    /// Moves between neighboring cells follow the movement in options, and straight lines between turns follow
+    /// its corner rules. `h` is always the straight-line distance, scaled by the weight in options.
+    /// Cell costs other than `IMPASSABLE` are ignored, since a line through a cell has no single step to charge for
+    pub fn find_any_angle_path(
+        &self,
+        start: Point,
+        end: Point,
+        variant: AnyAngle,
+        options: &SearchOptions,
+    ) -> AnyAnglePath {
+        if let Some(outcome) = self.check_agent_endpoints(&start, &end, options.agent_size) {
+            return AnyAnglePath {
+                outcome,
+                path: Vec::new(),
+                cost: 0.0,
+                expanded: 0,
+                partial: false,
+            };
+        }
+
+        let graph = self.graph_for(options);
+        let budget = Budget::new(options);
+        let estimate = |pos: Point| distance(pos, end) * f64::from(options.weight);
+        let mut best_g = vec![f64::INFINITY; self.len()];
+        let mut parents: Vec<Option<usize>> = vec![None; self.len()];
+        let mut closed = vec![false; self.len()];
+        let mut open = BinaryHeap::new();
+        let mut expanded = 0;
+        // The expanded cell with the lowest h, and that h
+        let mut closest: Option<(usize, f64)> = None;
+
+        let start_index = self.index(&start);
+        best_g[start_index] = 0.0;
+        open.push(Entry {
+            index: start_index,
+            g: 0.0,
+            h: estimate(start),
+            f: estimate(start),
+        });
+
+        let outcome = loop {
+            let Some(entry) = open.pop() else {
+                break SearchOutcome::Unreachable;
+            };
+            if closed[entry.index] || entry.g > best_g[entry.index] {
+                continue;
+            }
+            if budget.too_costly(entry.g.ceil() as i32) {
+                break SearchOutcome::BudgetExceeded;
+            }
+
+            let index = entry.index;
+            let pos = self.point(index);
+            if variant == AnyAngle::LazyThetaStar {
+                self.set_vertex(&graph, index, &mut best_g, &mut parents, &closed);
+            }
+            if pos == end {
+                closest = Some((index, 0.0));
+                break SearchOutcome::Found;
+            }
+            if budget.exhausted(expanded) {
+                break SearchOutcome::BudgetExceeded;
+            }
+
+            closed[index] = true;
+            expanded += 1;
+            if closest.is_none_or(|(_, h)| entry.h < h) {
+                closest = Some((index, entry.h));
+            }
+
+            for (next, _) in graph.successors(pos) {
+                let next_index = self.index(&next);
+                if closed[next_index] {
+                    continue;
+                }
+
+                // Skip over this cell when the next one can be seen from its parent
+                let from = match parents[index] {
+                    Some(parent)
+                        if variant == AnyAngle::LazyThetaStar
+                            || self.line_of_sight(self.point(parent), next, &graph) =>
+                    {
+                        parent
+                    }
+                    _ => index,
+                };
+                let g = best_g[from] + distance(self.point(from), next);
+                if g < best_g[next_index] {
+                    let h = estimate(next);
+                    best_g[next_index] = g;
+                    parents[next_index] = Some(from);
+                    open.push(Entry {
+                        index: next_index,
+                        g,
+                        h,
+                        f: g + h,
+                    });
+                }
+            }
+        };
+
+        let end_index = match outcome {
+            SearchOutcome::Found | SearchOutcome::BudgetExceeded => closest,
+            _ if options.partial => closest,
+            _ => None,
+        }
+        .map(|(index, _)| index);
+
+        let mut path = Vec::new();
+        let mut parent = end_index;
+        while let Some(index) = parent {
+            path.push(Waypoint::from(self.point(index)));
+            parent = parents[index];
+        }
+        path.reverse();
+
+        AnyAnglePath {
+            partial: outcome != SearchOutcome::Found && !path.is_empty(),
+            outcome,
+            path,
+            cost: end_index.map_or(0.0, |index| best_g[index]),
+            expanded,
+        }
+    }
+}
+
+impl Grid {
+    /// Lazy Theta* assumed the parent of index could see it. If it cannot, fall back to the
+    /// cheapest expanded neighbor, one of which always found index
+    fn set_vertex(
+        &self,
+        graph: &GridGraph,
+        index: usize,
+        best_g: &mut [f64],
+        parents: &mut [Option<usize>],
+        closed: &[bool],
+    ) {
+        let Some(parent) = parents[index] else {
+            return;
+        };
+        let pos = self.point(index);
+        if self.line_of_sight(self.point(parent), pos, graph) {
+            return;
+        }
+
+        let cheapest = graph
+            .predecessors(pos)
+            .map(|(previous, _)| self.index(&previous))
+            .filter(|previous| closed[*previous])
+            .map(|previous| {
+                (
+                    previous,
+                    best_g[previous] + distance(self.point(previous), pos),
+                )
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((previous, g)) = cheapest {
+            parents[index] = Some(previous);
+            best_g[index] = g;
+        }
+    }
+}
+
+/// Straight-line distance between the centers of two cells, in tenths of a cell
+fn distance(from: Point, to: Point) -> f64 {
+    let dx = f64::from(from.x - to.x);
+    let dy = f64::from(from.y - to.y);
+    dx.hypot(dy) * f64::from(ORTHOGONAL_COST)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_grid, Rng, MOVEMENTS};
+
+    /// The cell a Waypoint is the center of
+    fn cell(waypoint: &Waypoint) -> Point {
+        Point {
+            x: waypoint.x.floor() as i32,
+            y: waypoint.y.floor() as i32,
+        }
+    }
+
+    #[test]
+    fn paths_are_straight_lines_between_visible_turns() {
+        let mut rng = Rng::new(22);
+        for _ in 0..300 {
+            // Costs are ignored, but must not change which ends can be reached
+            let costs = rng.one_in(2);
+            let grid = random_grid(&mut rng, 16, 16, 4, costs);
+            let (start, end) = (rng.point(&grid), rng.point(&grid));
+            for movement in MOVEMENTS {
+                let options = SearchOptions {
+                    movement,
+                    ..SearchOptions::default()
+                };
+                let expected = grid.find_path_with(start, end, &options);
+                for variant in [AnyAngle::ThetaStar, AnyAngle::LazyThetaStar] {
+                    let result = grid.find_any_angle_path(start, end, variant, &options);
+                    assert_eq!(result.outcome, expected.outcome, "{variant:?} {movement:?}");
+                    if result.outcome != SearchOutcome::Found {
+                        assert!(result.path.is_empty());
+                        continue;
+                    }
+
+                    let turns: Vec<Point> = result.path.iter().map(cell).collect();
+                    assert_eq!(result.path.first(), Some(&Waypoint::from(start)));
+                    assert_eq!(result.path.last(), Some(&Waypoint::from(end)));
+                    let graph = grid.graph(movement);
+                    for pair in turns.windows(2) {
+                        assert!(
+                            grid.line_of_sight(pair[0], pair[1], &graph),
+                            "{variant:?} {movement:?} from {} to {}",
+                            pair[0],
+                            pair[1]
+                        );
+                    }
+                    let length: f64 = turns
+                        .windows(2)
+                        .map(|pair| distance(pair[0], pair[1]))
+                        .sum();
+                    assert!((result.cost - length).abs() < 1e-6);
+                }
+            }
+        }
+    }
+}